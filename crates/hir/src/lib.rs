@@ -3461,6 +3461,13 @@ impl Type {
         )
     }
 
+    /// Checks whether the type implements `trait_` with any generic arguments of the trait.
+    pub fn impls_trait_with_any_args(&self, db: &dyn HirDatabase, trait_: Trait) -> bool {
+        let canonical_ty =
+            Canonical { value: self.ty.clone(), binders: CanonicalVarKinds::empty(Interner) };
+        method_resolution::implements_trait(&canonical_ty, db, self.env.clone(), trait_.id)
+    }
+
     pub fn impls_trait(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) -> bool {
         let mut it = args.iter().map(|t| t.ty.clone());
         let trait_ref = TyBuilder::trait_ref(db, trait_.id)
@@ -15,4 +15,13 @@ pub struct AssistConfig {
     pub insert_use: InsertUseConfig,
     pub prefer_no_std: bool,
     pub assist_emit_must_use: bool,
+    /// Maximum width of the lines produced by assists, if limited.
+    pub max_width: Option<usize>,
+    /// Whether `qualify_path` only offers the shortest qualification, rather than all of them.
+    pub qualify_shortest_path_only: bool,
+    /// Whether `qualify_path` offers the full path of the imported name a path starts with.
+    pub qualify_imported_path_start: bool,
+    /// Whether `qualify_path` offers calling an unresolved method through its trait, like
+    /// `Trait::method(&x)`.
+    pub qualify_trait_methods: bool,
 }
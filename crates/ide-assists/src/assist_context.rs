@@ -2,7 +2,7 @@
 
 use hir::Semantics;
 use ide_db::base_db::{FileId, FileRange};
-use ide_db::{label::Label, RootDatabase};
+use ide_db::{label::Label, FxHashSet, RootDatabase};
 use syntax::{
     algo::{self, find_node_at_offset, find_node_at_range},
     AstNode, AstToken, Direction, SourceFile, SyntaxElement, SyntaxKind, SyntaxToken, TextRange,
@@ -133,6 +133,8 @@ pub(crate) struct Assists {
     resolve: AssistResolveStrategy,
     buf: Vec<Assist>,
     allowed: Option<Vec<AssistKind>>,
+    /// The group and label of the grouped assists added so far, see [`Assists::add_impl`].
+    group_labels: FxHashSet<(String, String)>,
 }
 
 impl Assists {
@@ -142,6 +144,7 @@ impl Assists {
             file: ctx.frange.file_id,
             buf: Vec::new(),
             allowed: ctx.config.allowed.clone(),
+            group_labels: FxHashSet::default(),
         }
     }
 
@@ -158,7 +161,7 @@ impl Assists {
         f: impl FnOnce(&mut SourceChangeBuilder),
     ) -> Option<()> {
         let mut f = Some(f);
-        self.add_impl(None, id, label.into(), target, &mut |it| f.take().unwrap()(it))
+        self.add_impl(None, id, label.into(), target, &[], &mut |it| f.take().unwrap()(it))
     }
 
     pub(crate) fn add_group(
@@ -170,7 +173,23 @@ impl Assists {
         f: impl FnOnce(&mut SourceChangeBuilder),
     ) -> Option<()> {
         let mut f = Some(f);
-        self.add_impl(Some(group), id, label.into(), target, &mut |it| f.take().unwrap()(it))
+        self.add_impl(Some(group), id, label.into(), target, &[], &mut |it| f.take().unwrap()(it))
+    }
+
+    /// Like [`Assists::add_group`], for a fix of the compiler diagnostics with the given codes.
+    pub(crate) fn add_group_fixing(
+        &mut self,
+        group: &GroupLabel,
+        id: AssistId,
+        label: impl Into<String>,
+        target: TextRange,
+        diagnostic_codes: &'static [&'static str],
+        f: impl FnOnce(&mut SourceChangeBuilder),
+    ) -> Option<()> {
+        let mut f = Some(f);
+        self.add_impl(Some(group), id, label.into(), target, diagnostic_codes, &mut |it| {
+            f.take().unwrap()(it)
+        })
     }
 
     fn add_impl(
@@ -179,12 +198,21 @@ impl Assists {
         id: AssistId,
         label: String,
         target: TextRange,
+        diagnostic_codes: &'static [&'static str],
         f: &mut dyn FnMut(&mut SourceChangeBuilder),
     ) -> Option<()> {
         if !self.is_allowed(&id) {
             return None;
         }
 
+        // Different candidates of a group can still read the same, e.g. via re-exports, only show
+        // them once. Their edits aren't compared, as they are only computed on resolve.
+        if let Some(group) = group {
+            if !self.group_labels.insert((group.0.clone(), label.clone())) {
+                return None;
+            }
+        }
+
         let mut trigger_signature_help = false;
         let source_change = if self.resolve.should_resolve(&id) {
             let mut builder = SourceChangeBuilder::new(self.file);
@@ -197,7 +225,15 @@ impl Assists {
 
         let label = Label::new(label);
         let group = group.cloned();
-        self.buf.push(Assist { id, label, group, target, source_change, trigger_signature_help });
+        self.buf.push(Assist {
+            id,
+            label,
+            group,
+            target,
+            source_change,
+            trigger_signature_help,
+            diagnostic_codes,
+        });
         Some(())
     }
 
@@ -150,7 +150,11 @@ pub(crate) fn auto_import(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<
 pub(super) fn find_importable_node(
     ctx: &AssistContext<'_>,
 ) -> Option<(ImportAssets, SyntaxElement)> {
-    if let Some(path_under_caret) = ctx.find_node_at_offset_with_descend::<ast::Path>() {
+    if let Some(mut path_under_caret) = ctx.find_node_at_offset_with_descend::<ast::Path>() {
+        // `HashMap::$0` is still being typed, look at the part before the trailing `::`
+        if path_under_caret.segment().is_none() {
+            path_under_caret = path_under_caret.qualifier()?;
+        }
         ImportAssets::for_exact_path(&path_under_caret, &ctx.sema)
             .zip(Some(path_under_caret.syntax().clone().into()))
     } else if let Some(method_under_caret) =
@@ -1307,6 +1311,27 @@ mod bar {
     }
 }
 use foo::Foo$0;
+",
+        );
+    }
+
+    #[test]
+    fn applicable_with_trailing_colons() {
+        check_assist(
+            auto_import,
+            r"
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = HashMap::$0;
+}
+",
+            r"
+use std::collections::HashMap;
+
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = HashMap::;
+}
 ",
         );
     }
@@ -1,15 +1,16 @@
-use std::iter;
+use std::{iter, time::Instant};
 
-use hir::AsAssocItem;
+use hir::{AsAssocItem, ModuleDef, PathResolution, ScopeDef};
 use ide_db::RootDatabase;
 use ide_db::{
+    base_db::{Edition, FileId, FileRange, SourceDatabaseExt},
     helpers::mod_path_to_ast,
-    imports::import_assets::{ImportCandidate, LocatedImport},
+    imports::import_assets::{ImportAssets, ImportCandidate, LocatedImport, PathImportCandidate},
 };
 use syntax::{
     ast,
-    ast::{make, HasArgList},
-    AstNode, NodeOrToken,
+    ast::{make, HasArgList, HasModuleItem, HasName},
+    match_ast, AstNode, NodeOrToken, SyntaxElement, SyntaxKind, TextRange, TextSize, T,
 };
 
 use crate::{
@@ -36,78 +37,849 @@ use crate::{
 // # pub mod std { pub mod collections { pub struct HashMap { } } }
 // ```
 pub(crate) fn qualify_path(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
-    let (import_assets, syntax_under_caret) = find_importable_node(ctx)?;
+    let (import_assets, syntax_under_caret) = match find_importable_node(ctx) {
+        Some(it) => it,
+        None => {
+            // The path resolves, which only leaves the offers for ambiguous globs and imported
+            // path starts. Tell them apart by the syntax before resolving anything else.
+            let path = ctx.find_node_at_offset::<ast::Path>()?;
+            let outermost = path.syntax().ancestors().map_while(ast::Path::cast).last()?;
+            if path.qualifier().is_none()
+                && qualify_ambiguous_glob_import(acc, ctx, &path).is_some()
+            {
+                return Some(());
+            }
+            if !ctx.config.qualify_imported_path_start || outermost.qualifier().is_none() {
+                return None;
+            }
+            return qualify_imported_path_start(acc, ctx, &outermost);
+        }
+    };
+    let frange = original_range(ctx, &syntax_under_caret);
+    let range = frange.range;
+    let candidate = import_assets.import_candidate();
+    // The edit replaces the whole path or call, but the assist targets the unresolved part only.
+    let target = qualify_target(ctx, candidate, &syntax_under_caret).unwrap_or(range);
+    let (qualify_candidate, offered_imports) =
+        offered_imports(ctx, &import_assets, syntax_under_caret, frange)?;
+    if offered_imports.is_empty() {
+        return None;
+    }
+
+    let krate = import_assets.module_with_candidate().krate();
+    let builtin_group_label = group_label(candidate, true);
+    let group_label = group_label(candidate, false);
+    for import in offered_imports {
+        // keep std, core and alloc candidates apart from the workspace ones
+        let builtin =
+            import.original_item.krate(ctx.db()).map_or(false, |it| it.is_builtin(ctx.db()));
+        let extern_crate = missing_extern_crate(ctx, krate, &import.import_path);
+        let label = match &extern_crate {
+            Some((.., extern_crate)) => {
+                format!("{} and add `{}`", label(candidate, &import), extern_crate.trim_end())
+            }
+            None => label(candidate, &import),
+        };
+        acc.add_group_fixing(
+            if builtin { &builtin_group_label } else { &group_label },
+            AssistId("qualify_path", AssistKind::QuickFix),
+            label,
+            target,
+            fixed_diagnostics(candidate),
+            |builder| {
+                qualify_candidate.qualify(
+                    |replace_with: String| builder.replace(range, replace_with),
+                    &import.import_path,
+                    import.item_to_import,
+                );
+                if let Some((file_id, offset, extern_crate)) = extern_crate {
+                    builder.edit_file(file_id);
+                    builder.insert(offset, extern_crate);
+                }
+            },
+        );
+    }
+    Some(())
+}
+
+fn replacement(qualify_candidate: &QualifyCandidate<'_>, import: &LocatedImport) -> String {
+    let mut replacement = String::new();
+    qualify_candidate.qualify(|it| replacement = it, &import.import_path, import.item_to_import);
+    replacement
+}
+
+/// The range of the unresolved path or method call in the file the user edits.
+fn original_range(ctx: &AssistContext<'_>, syntax: &SyntaxElement) -> FileRange {
+    match syntax {
+        NodeOrToken::Node(node) => ctx.sema.original_range(node),
+        NodeOrToken::Token(token) => {
+            FileRange { file_id: ctx.file_id(), range: token.text_range() }
+        }
+    }
+}
+
+/// Whether qualifying the path or method call at `range` with `import` is worth offering.
+fn is_offered(
+    ctx: &AssistContext<'_>,
+    qualify_candidate: &QualifyCandidate<'_>,
+    import: &LocatedImport,
+    range: FileRange,
+) -> bool {
+    if qualify_candidate.is_noop(&import.import_path) {
+        cov_mark::hit!(qualify_path_noop);
+        return false;
+    }
+    // only the lines of the current file are measured, not the ones of a macro definition or an
+    // included file
+    if let (Some(max_width), true) = (ctx.config.max_width, range.file_id == ctx.file_id()) {
+        let text = ctx.db().file_text(ctx.file_id());
+        if !fits_max_width(&text, range.range, &replacement(qualify_candidate, import), max_width) {
+            // leave it to `auto_import`, which keeps the line short
+            cov_mark::hit!(qualify_path_exceeds_max_width);
+            return false;
+        }
+    }
+    true
+}
+
+/// Crates of the 2015 edition only see the dependencies declared with `extern crate` in their
+/// root, so qualifying with a dependency has to declare it as well.
+fn missing_extern_crate(
+    ctx: &AssistContext<'_>,
+    krate: hir::Crate,
+    import_path: &hir::ModPath,
+) -> Option<(FileId, TextSize, String)> {
+    if krate.edition(ctx.db()) != Edition::Edition2015 || import_path.kind != hir::PathKind::Plain {
+        return None;
+    }
+    let name = import_path.segments().first()?.to_smol_str();
+    // the standard library is injected implicitly
+    if matches!(name.as_str(), "std" | "core") {
+        return None;
+    }
+    krate.dependencies(ctx.db()).into_iter().find(|dep| dep.name.to_smol_str() == name)?;
+
+    let root_file_id = krate.root_file(ctx.db());
+    let root = ctx.sema.parse(root_file_id);
+    let declared = root.items().any(|item| match item {
+        ast::Item::ExternCrate(extern_crate) => {
+            let declared_name = match extern_crate.rename() {
+                Some(rename) => rename.name().map(|it| it.text().to_string()),
+                None => extern_crate.name_ref().map(|it| it.text().to_string()),
+            };
+            declared_name.as_deref() == Some(name.as_str())
+        }
+        _ => false,
+    });
+    if declared {
+        return None;
+    }
+    let offset = match root.items().next() {
+        Some(item) => item.syntax().text_range().start(),
+        None => root.syntax().text_range().end(),
+    };
+    Some((root_file_id, offset, format!("extern crate {name};\n")))
+}
+
+/// The number of steps in the module tree between `from` and `to`, `usize::MAX` if they are
+/// in different crates.
+fn module_distance(db: &RootDatabase, from: hir::Module, to: hir::Module) -> usize {
+    let to_root = to.path_to_root(db);
+    from.path_to_root(db)
+        .iter()
+        .enumerate()
+        .find_map(|(up, ancestor)| Some(up + to_root.iter().position(|it| it == ancestor)?))
+        .unwrap_or(usize::MAX)
+}
+
+/// The paths [`qualify_path`] offers to qualify the unresolved name under the caret with.
+pub(crate) fn qualification_candidates(ctx: &AssistContext<'_>) -> Vec<hir::ModPath> {
+    let (import_assets, syntax_under_caret) = match find_importable_node(ctx) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    proposed_imports(ctx, &import_assets, &syntax_under_caret)
+        .into_iter()
+        .map(|import| import.import_path)
+        .collect()
+}
+
+/// The edits [`qualify_path`] offers for the unresolved name under the caret, one per candidate,
+/// without the extern crate declarations some of them need in 2015 edition crates.
+pub(crate) fn qualification_edits(ctx: &AssistContext<'_>) -> Vec<(TextRange, String)> {
+    let (import_assets, syntax_under_caret) = match find_importable_node(ctx) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let frange = original_range(ctx, &syntax_under_caret);
+    let (qualify_candidate, offered_imports) =
+        match offered_imports(ctx, &import_assets, syntax_under_caret, frange) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+    offered_imports
+        .iter()
+        .map(|import| (frange.range, replacement(&qualify_candidate, import)))
+        .collect()
+}
+
+/// The candidates [`qualify_path`] offers for `syntax`, the unresolved path or method call at
+/// `range`, along with how to qualify it with them.
+fn offered_imports<'db>(
+    ctx: &AssistContext<'db>,
+    import_assets: &ImportAssets,
+    syntax: SyntaxElement,
+    range: FileRange,
+) -> Option<(QualifyCandidate<'db>, Vec<LocatedImport>)> {
+    let proposed_imports = proposed_imports(ctx, import_assets, &syntax);
+    let qualify_candidate = find_qualify_candidate(ctx, import_assets.import_candidate(), syntax)?;
+    let offered_imports = proposed_imports
+        .into_iter()
+        .filter(|import| is_offered(ctx, &qualify_candidate, import, range))
+        .collect();
+    Some((qualify_candidate, offered_imports))
+}
+
+fn proposed_imports(
+    ctx: &AssistContext<'_>,
+    import_assets: &ImportAssets,
+    syntax_under_caret: &SyntaxElement,
+) -> Vec<LocatedImport> {
+    if let ImportCandidate::TraitMethod(_) = import_assets.import_candidate() {
+        if !ctx.config.qualify_trait_methods {
+            cov_mark::hit!(qualify_path_trait_methods_disabled);
+            return Vec::new();
+        }
+    }
+    // only measure the search when tracing asks for that much detail
+    let start = tracing::enabled!(tracing::Level::TRACE).then(Instant::now);
+    let prefer_no_std = ctx.config.prefer_no_std;
+    // the import style decides how a path starts once, rather than searching for each style
     let mut proposed_imports =
-        import_assets.search_for_relative_paths(&ctx.sema, ctx.config.prefer_no_std);
-    if proposed_imports.is_empty() {
+        match (import_assets.import_candidate(), ctx.config.insert_use.prefix_kind) {
+            (
+                ImportCandidate::Path(_),
+                prefix_kind @ (hir::PrefixKind::ByCrate | hir::PrefixKind::BySelf),
+            ) => import_assets.search_for_imports(&ctx.sema, prefix_kind, prefer_no_std),
+            _ => import_assets.search_for_relative_paths(&ctx.sema, prefer_no_std),
+        };
+    match start {
+        Some(start) => tracing::debug!(
+            candidates = proposed_imports.len(),
+            elapsed_us = start.elapsed().as_micros() as u64,
+            "qualify_path: searched for paths"
+        ),
+        None => {
+            tracing::debug!(candidates = proposed_imports.len(), "qualify_path: searched for paths")
+        }
+    }
+
+    if let Some(variant) = matched_variant(ctx, import_assets, syntax_under_caret) {
+        if proposed_imports.iter().all(|it| it.item_to_import != variant.item_to_import) {
+            proposed_imports.push(variant);
+        }
+    }
+
+    // we aren't interested in different namespaces
+    proposed_imports.dedup_by(|a, b| a.import_path == b.import_path);
+    // an alias of another candidate names the very same type, only offer the definition
+    let adts: Vec<hir::Adt> = proposed_imports
+        .iter()
+        .filter_map(|import| match import.original_item.as_module_def()? {
+            ModuleDef::Adt(adt) => Some(adt),
+            _ => None,
+        })
+        .collect();
+    proposed_imports.retain(|import| match import.original_item.as_module_def() {
+        Some(ModuleDef::TypeAlias(alias)) => !alias
+            .ty(ctx.db())
+            .as_adt()
+            .map_or(false, |adt| adts.contains(&adt) && !adt.has_non_default_type_params(ctx.db())),
+        _ => true,
+    });
+    // a trait and a type of the same name can't both fit where the name is used
+    if let ImportCandidate::Path(PathImportCandidate { qualifier: None, .. }) =
+        import_assets.import_candidate()
+    {
+        if let Some(expects_trait) = expects_trait(syntax_under_caret) {
+            let fits = |import: &LocatedImport| {
+                matches!(import.original_item.as_module_def(), Some(ModuleDef::Trait(_)))
+                    == expects_trait
+            };
+            if proposed_imports.iter().any(fits) {
+                cov_mark::hit!(qualify_path_fitting_namespace);
+                proposed_imports.retain(fits);
+            }
+        }
+    }
+    let module = import_assets.module_with_candidate();
+    if let ImportCandidate::TraitMethod(_) = import_assets.import_candidate() {
+        // offer the traits declared closest to the call site first, and equally close ones by
+        // their path, so that the order doesn't depend on the search
+        proposed_imports.sort_by_cached_key(|import| {
+            let trait_module =
+                import.item_to_import.as_module_def().and_then(|it| it.module(ctx.db()));
+            let distance =
+                trait_module.map_or(usize::MAX, |it| module_distance(ctx.db(), module, it));
+            (distance, import.import_path.to_string())
+        });
+    }
+    if ctx.config.qualify_shortest_path_only {
+        // fewest segments first, then the shortest text, and the first one found on a tie
+        proposed_imports = proposed_imports
+            .into_iter()
+            .min_by_key(|it| (it.import_path.len(), it.import_path.to_string().len()))
+            .into_iter()
+            .collect();
+    }
+
+    proposed_imports
+}
+
+/// Enum variants aren't found by the import search, but a pattern can be qualified with the
+/// variant of the matched enum, like `Circle(r)` with `shapes::Shape::Circle` when matching a
+/// `Shape`.
+fn matched_variant(
+    ctx: &AssistContext<'_>,
+    import_assets: &ImportAssets,
+    syntax_under_caret: &SyntaxElement,
+) -> Option<LocatedImport> {
+    let ImportCandidate::Path(PathImportCandidate { qualifier: None, name }) =
+        import_assets.import_candidate()
+    else {
+        return None;
+    };
+    let path = ast::Path::cast(syntax_under_caret.as_node()?.clone())?;
+    let mut pat = ast::Pat::cast(path.syntax().parent()?)?.syntax().clone();
+    // the pattern is matched against the scrutinee itself, possibly as one of several alternatives
+    while let Some(parent) = pat.parent().filter(|it| ast::OrPat::can_cast(it.kind())) {
+        pat = parent;
+    }
+    let scrutinee = match_ast! {
+        match (pat.parent()?) {
+            ast::MatchArm(it) => it.syntax().ancestors().find_map(ast::MatchExpr::cast)?.expr(),
+            ast::LetStmt(it) => it.initializer(),
+            ast::LetExpr(it) => it.expr(),
+            _ => None,
+        }
+    }?;
+    let ty = ctx.sema.type_of_expr(&scrutinee)?.original.strip_references();
+    let hir::Adt::Enum(enum_) = ty.as_adt()? else { return None };
+    let variant = enum_
+        .variants(ctx.db())
+        .into_iter()
+        .find(|it| it.name(ctx.db()).to_smol_str() == name.text())?;
+    let import_path = import_assets.module_with_candidate().find_use_path(
+        ctx.db(),
+        ModuleDef::Variant(variant),
+        ctx.config.prefer_no_std,
+    )?;
+    let item = hir::ItemInNs::from(ModuleDef::Variant(variant));
+    Some(LocatedImport::new(import_path, item, item, None))
+}
+
+/// Whether the unresolved path names a trait, like in a bound or the trait of an impl, rather
+/// than a type or a value. `None` if its position doesn't tell.
+fn expects_trait(syntax_under_caret: &SyntaxElement) -> Option<bool> {
+    let path = ast::Path::cast(syntax_under_caret.as_node()?.clone())?;
+    let parent = path.syntax().parent()?;
+    if ast::PathExpr::can_cast(parent.kind()) {
+        return Some(false);
+    }
+    let path_type = ast::PathType::cast(parent)?;
+    let is_trait = match path_type.syntax().parent() {
+        Some(parent) if ast::TypeBound::can_cast(parent.kind()) => true,
+        Some(parent) => ast::Impl::cast(parent)
+            .and_then(|impl_| impl_.trait_())
+            .map_or(false, |trait_| trait_.syntax() == path_type.syntax()),
+        None => false,
+    };
+    Some(is_trait)
+}
+
+/// Whether the lines around `range` stay within `max_width` once it is replaced. Lines which
+/// already exceed it aren't held against the replacement.
+fn fits_max_width(text: &str, range: TextRange, replacement: &str, max_width: usize) -> bool {
+    let (start, end) = (usize::from(range.start()), usize::from(range.end()));
+    let (Some(before), Some(replaced), Some(after)) =
+        (text.get(..start), text.get(start..end), text.get(end..))
+    else {
+        return true;
+    };
+    let before = &before[before.rfind('\n').map_or(0, |it| it + 1)..];
+    let after = &after[..after.find('\n').unwrap_or(after.len())];
+    let width = |lines: &str| lines.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    width(&format!("{before}{replaced}{after}")) > max_width
+        || width(&format!("{before}{replacement}{after}")) <= max_width
+}
+
+/// Offers to spell out the full path of the imported name a resolved path starts with, like
+/// `mod1::mod2::Item` for `mod2::Item` after `use mod1::mod2;`, if the
+/// `qualify_imported_path_start` setting is enabled.
+fn qualify_imported_path_start(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+    path: &ast::Path,
+) -> Option<()> {
+    let first = path.first_qualifier_or_self();
+    if first == *path || !matches!(first.segment()?.kind()?, ast::PathSegmentKind::Name(_)) {
+        return None;
+    }
+    let def = match ctx.sema.resolve_path(&first)? {
+        PathResolution::Def(def) => def,
+        _ => return None,
+    };
+    let scope = ctx.sema.scope(path.syntax())?;
+    // names declared in the current module don't come from an import
+    let parent = match def {
+        ModuleDef::Module(module) => module.parent(ctx.db()),
+        _ => def.module(ctx.db()),
+    };
+    if parent == Some(scope.module()) {
         return None;
     }
 
-    let range = match &syntax_under_caret {
-        NodeOrToken::Node(node) => ctx.sema.original_range(node).range,
-        NodeOrToken::Token(token) => token.text_range(),
+    let canonical = def.canonical_path(ctx.db())?;
+    let krate = def.module(ctx.db())?.krate();
+    let full_paths = if krate == scope.krate() {
+        vec![canonical.clone(), format!("crate::{canonical}")]
+    } else {
+        vec![format!("{}::{canonical}", krate.display_name(ctx.db())?.crate_name())]
     };
-    let candidate = import_assets.import_candidate();
+    let full_path = full_paths.into_iter().find(|it| {
+        scope.speculative_resolve(&make::path_from_text(it)) == Some(PathResolution::Def(def))
+    })?;
+    if first.syntax().text() == full_path.as_str() {
+        return None;
+    }
+    let rest = path.syntax().text().slice(first.syntax().text_range().len()..).to_string();
+    let qualified = format!("{full_path}{rest}");
+
+    let range = ctx.sema.original_range(path.syntax()).range;
+    acc.add(
+        AssistId("qualify_path", AssistKind::QuickFix),
+        format!("Qualify with `{qualified}`"),
+        range,
+        |builder| builder.replace(range, qualified),
+    )
+}
+
+/// Name resolution picks one of the items when several glob imports bring in the same name,
+/// while rustc reports the name as ambiguous. Offer to qualify it with each of the globs.
+fn qualify_ambiguous_glob_import(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+    path: &ast::Path,
+) -> Option<()> {
+    let segment = path.segment()?;
+    let name = segment.name_ref()?.text().to_string();
+
+    // Only module level globs are considered, and any item or explicit import of the name
+    // shadows them.
+    let container = path.syntax().ancestors().find(|it| {
+        ast::SourceFile::can_cast(it.kind())
+            || (ast::ItemList::can_cast(it.kind())
+                && it.parent().map_or(false, |it| ast::Module::can_cast(it.kind())))
+    })?;
+    // it takes two globs to be ambiguous, most modules have none
+    container
+        .children()
+        .filter_map(ast::Use::cast)
+        .flat_map(|it| it.syntax().descendants().filter_map(ast::UseTree::cast))
+        .filter(|it| it.star_token().is_some())
+        .nth(1)?;
+    let mut globs = Vec::new();
+    for item in container.children().filter_map(ast::Item::cast) {
+        match item {
+            ast::Item::Use(use_item) => {
+                for use_tree in use_item.syntax().descendants().filter_map(ast::UseTree::cast) {
+                    if use_tree.star_token().is_some() {
+                        globs.extend(glob_path(&use_tree));
+                    } else if use_tree.use_tree_list().is_none()
+                        && imported_name(&use_tree).as_deref() == Some(&*name)
+                    {
+                        return None;
+                    }
+                }
+            }
+            item => {
+                let item = ast::AnyHasName::cast(item.syntax().clone());
+                if item.and_then(|it| it.name()).map_or(false, |it| it.text() == name) {
+                    return None;
+                }
+            }
+        }
+    }
+
+    if globs.len() < 2 {
+        return None;
+    }
+    let resolved = match ctx.sema.resolve_path(path)? {
+        PathResolution::Def(def) => def,
+        _ => return None,
+    };
+
+    // a name in another namespace than the one the path is resolved in doesn't compete
+    let namespace = path_namespace(path);
+    let scope = ctx.sema.scope(path.syntax())?;
+    let module = scope.module();
+    let mut candidates: Vec<(ast::Path, ModuleDef)> = Vec::new();
+    for glob in globs {
+        let glob_module = match scope.speculative_resolve(&glob) {
+            Some(PathResolution::Def(ModuleDef::Module(it))) => it,
+            _ => continue,
+        };
+        for (item_name, def) in glob_module.scope(ctx.db(), Some(module)) {
+            if let ScopeDef::ModuleDef(def) = def {
+                if item_name.to_smol_str() == name
+                    && is_in_namespace(ctx.db(), def, namespace)
+                    && candidates.iter().all(|(_, it)| *it != def)
+                {
+                    candidates.push((glob.clone(), def));
+                }
+            }
+        }
+    }
+    if candidates.len() < 2 || candidates.iter().all(|(_, def)| *def != resolved) {
+        return None;
+    }
+
+    let group_label = GroupLabel(format!("Qualify {name}"));
+    let range = path.syntax().text_range();
+    for (glob, _) in candidates {
+        acc.add_group_fixing(
+            &group_label,
+            AssistId("qualify_path", AssistKind::QuickFix),
+            format!("Qualify as `{glob}::{name}`"),
+            range,
+            UNRESOLVED_PATH_CODES,
+            |builder| builder.replace(range, format!("{glob}::{segment}")),
+        );
+    }
+    Some(())
+}
+
+/// The path of the module the glob `use_tree` imports from, including the paths of the use trees
+/// it is nested in, like `a::b` for `use a::{b::*, c::*};`.
+fn glob_path(use_tree: &ast::UseTree) -> Option<ast::Path> {
+    let mut segments: Vec<String> = use_tree
+        .syntax()
+        .ancestors()
+        .filter_map(ast::UseTree::cast)
+        .filter_map(|it| it.path())
+        .map(|it| it.to_string())
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    segments.reverse();
+    Some(make::path_from_text(&segments.join("::")))
+}
+
+/// The namespace `path` is resolved in, judging from where it is used.
+fn path_namespace(path: &ast::Path) -> hir::Namespace {
+    match path.syntax().parent().map(|it| it.kind()) {
+        Some(SyntaxKind::PATH_EXPR | SyntaxKind::PATH_PAT | SyntaxKind::TUPLE_STRUCT_PAT) => {
+            hir::Namespace::Values
+        }
+        Some(SyntaxKind::MACRO_CALL) => hir::Namespace::Macros,
+        _ => hir::Namespace::Types,
+    }
+}
+
+fn is_in_namespace(db: &RootDatabase, def: ModuleDef, namespace: hir::Namespace) -> bool {
+    let also_value = match def {
+        ModuleDef::Function(_) | ModuleDef::Const(_) | ModuleDef::Static(_) => {
+            return namespace == hir::Namespace::Values
+        }
+        ModuleDef::Macro(_) => return namespace == hir::Namespace::Macros,
+        // unit and tuple structs are constructors as well
+        ModuleDef::Adt(hir::Adt::Struct(it)) => it.kind(db) != hir::StructKind::Record,
+        ModuleDef::Variant(it) => it.kind(db) != hir::StructKind::Record,
+        _ => false,
+    };
+    namespace == hir::Namespace::Types || (also_value && namespace == hir::Namespace::Values)
+}
+
+fn imported_name(use_tree: &ast::UseTree) -> Option<String> {
+    match use_tree.rename() {
+        Some(rename) => Some(rename.name()?.text().to_string()),
+        None => Some(use_tree.path()?.segment()?.name_ref()?.text().to_string()),
+    }
+}
+
+// Assist: qualify_all_paths
+//
+// Qualifies every unresolved path in the file which has exactly one possible qualification.
+// Paths with several possible qualifications are left untouched.
+//
+// ```
+// fn main() {
+//     let map = HashMap$0::new();
+//     let set = HashSet::new();
+// }
+// # pub mod std { pub mod collections { pub struct HashMap { } pub struct HashSet { } } }
+// ```
+// ->
+// ```
+// fn main() {
+//     let map = std::collections::HashMap::new();
+//     let set = std::collections::HashSet::new();
+// }
+// # pub mod std { pub mod collections { pub struct HashMap { } pub struct HashSet { } } }
+// ```
+pub(crate) fn qualify_all_paths(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    // Only offer the assist on an unresolved name, it would be noise everywhere else. The rest of
+    // the file is only searched once the assist is applied.
+    let target = picked_at_caret(ctx, pick_unambiguous)?;
+    acc.add(
+        AssistId("qualify_all_paths", AssistKind::QuickFix),
+        "Qualify all unambiguous paths in file",
+        target,
+        |builder| {
+            for (range, replace_with) in qualify_paths_in_file(ctx, pick_unambiguous) {
+                builder.replace(range, replace_with);
+            }
+        },
+    )
+}
+
+fn pick_unambiguous<'a>(_: &ast::Path, imports: &'a [LocatedImport]) -> Option<&'a LocatedImport> {
+    match imports {
+        [import] => Some(import),
+        _ => None,
+    }
+}
+
+// Assist: qualify_all_paths_through_imported_modules
+//
+// Qualifies every unresolved path in the file which can be qualified through exactly one module
+// that is brought into scope by an import, even if the item could also be qualified otherwise.
+//
+// ```
+// use std::fmt;
+//
+// fn write(_: &mut Formatter$0, _: &dyn Write) {}
+// # pub mod std { pub mod fmt { pub struct Formatter; pub trait Write {} } pub mod io { pub trait Write {} } }
+// ```
+// ->
+// ```
+// use std::fmt;
+//
+// fn write(_: &mut fmt::Formatter, _: &dyn fmt::Write) {}
+// # pub mod std { pub mod fmt { pub struct Formatter; pub trait Write {} } pub mod io { pub trait Write {} } }
+// ```
+pub(crate) fn qualify_all_paths_through_imported_modules(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    // Only offer the assist on an unresolved name, it would be noise everywhere else. The rest of
+    // the file is only searched once the assist is applied.
+    let target =
+        picked_at_caret(ctx, |path, imports| pick_through_imported_module(ctx, path, imports))?;
+    acc.add(
+        AssistId("qualify_all_paths_through_imported_modules", AssistKind::QuickFix),
+        "Qualify all paths through imported modules",
+        target,
+        |builder| {
+            let edits = qualify_paths_in_file(ctx, |path, imports| {
+                pick_through_imported_module(ctx, path, imports)
+            });
+            for (range, replace_with) in edits {
+                builder.replace(range, replace_with);
+            }
+        },
+    )
+}
+
+fn pick_through_imported_module<'a>(
+    ctx: &AssistContext<'_>,
+    path: &ast::Path,
+    imports: &'a [LocatedImport],
+) -> Option<&'a LocatedImport> {
+    let scope = ctx.sema.scope(path.syntax())?;
+    let mut through_imports = imports.iter().filter(|import| {
+        let import_path = &import.import_path;
+        if import_path.kind != hir::PathKind::Plain || import_path.len() < 2 {
+            return false;
+        }
+        let first = make::ext::ident_path(&import_path.segments()[0].to_smol_str());
+        // modules declared in the current one are in scope without an import
+        match scope.speculative_resolve(&first) {
+            Some(PathResolution::Def(ModuleDef::Module(module))) => {
+                module.parent(ctx.db()) != Some(scope.module())
+            }
+            _ => false,
+        }
+    });
+    let import = through_imports.next()?;
+    through_imports.next().is_none().then_some(import)
+}
+
+/// The target of a file-wide qualification, if `pick` chooses a candidate for the unresolved path
+/// under the caret among the ones [`qualify_path`] offers for it.
+fn picked_at_caret(
+    ctx: &AssistContext<'_>,
+    pick: impl for<'a> Fn(&ast::Path, &'a [LocatedImport]) -> Option<&'a LocatedImport>,
+) -> Option<TextRange> {
+    let (import_assets, syntax_under_caret) = find_importable_node(ctx)?;
+    let path = ast::Path::cast(syntax_under_caret.as_node()?.clone())?;
+    let frange = original_range(ctx, &syntax_under_caret);
+    let (_, offered_imports) = offered_imports(ctx, &import_assets, syntax_under_caret, frange)?;
+    pick(&path, &offered_imports)?;
+    Some(frange.range)
+}
+
+/// The most unresolved paths a file-wide qualification looks up, each one takes an import search.
+const MAX_UNRESOLVED_PATHS: usize = 100;
+
+/// Qualifies the unresolved paths of the file with the candidate chosen by `pick` among the ones
+/// [`qualify_path`] offers for them, if any. Only the first [`MAX_UNRESOLVED_PATHS`] unresolved
+/// paths are looked up.
+fn qualify_paths_in_file(
+    ctx: &AssistContext<'_>,
+    pick: impl for<'a> Fn(&ast::Path, &'a [LocatedImport]) -> Option<&'a LocatedImport>,
+) -> Vec<(TextRange, String)> {
+    let mut edits: Vec<(TextRange, String)> = Vec::new();
+    let Some(source_file) = ctx.find_node_at_offset::<ast::SourceFile>() else { return edits };
+    let mut unresolved = 0;
+    for path in source_file.syntax().descendants().filter_map(ast::Path::cast) {
+        let range = path.syntax().text_range();
+        // Qualifiers and other nested paths are covered by the replacement of the outer one.
+        if edits.iter().any(|(it, _)| it.contains_range(range)) {
+            continue;
+        }
+        let Some(import_assets) = ImportAssets::for_exact_path(&path, &ctx.sema) else { continue };
+        unresolved += 1;
+        if unresolved > MAX_UNRESOLVED_PATHS {
+            cov_mark::hit!(qualify_all_paths_capped);
+            break;
+        }
+        let Some((qualify_candidate, offered_imports)) = offered_imports(
+            ctx,
+            &import_assets,
+            path.syntax().clone().into(),
+            FileRange { file_id: ctx.file_id(), range },
+        ) else {
+            continue;
+        };
+        if let Some(import) = pick(&path, &offered_imports) {
+            edits.push((range, replacement(&qualify_candidate, import)));
+        }
+    }
+    edits
+}
+
+fn qualify_target(
+    ctx: &AssistContext<'_>,
+    candidate: &ImportCandidate,
+    syntax_under_caret: &SyntaxElement,
+) -> Option<TextRange> {
+    let node = syntax_under_caret.as_node()?.clone();
+    let target = match candidate {
+        ImportCandidate::Path(candidate) if candidate.qualifier.is_some() => {
+            ast::Path::cast(node)?.qualifier()?.syntax().clone()
+        }
+        ImportCandidate::Path(_) => return None,
+        ImportCandidate::TraitAssocItem(_) => ast::Path::cast(node)?.segment()?.syntax().clone(),
+        ImportCandidate::TraitMethod(_) => {
+            ast::MethodCallExpr::cast(node)?.name_ref()?.syntax().clone()
+        }
+    };
+    Some(ctx.sema.original_range(&target).range)
+}
+
+fn find_qualify_candidate<'db>(
+    ctx: &AssistContext<'db>,
+    candidate: &ImportCandidate,
+    syntax_under_caret: SyntaxElement,
+) -> Option<QualifyCandidate<'db>> {
     let qualify_candidate = match syntax_under_caret {
         NodeOrToken::Node(syntax_under_caret) => match candidate {
             ImportCandidate::Path(candidate) if candidate.qualifier.is_some() => {
                 cov_mark::hit!(qualify_path_qualifier_start);
                 let path = ast::Path::cast(syntax_under_caret)?;
-                let (prev_segment, segment) = (path.qualifier()?.segment()?, path.segment()?);
-                QualifyCandidate::QualifierStart(segment, prev_segment.generic_arg_list())
+                QualifyCandidate::QualifierStart(text_after_first_name(&path)?)
             }
             ImportCandidate::Path(_) => {
                 cov_mark::hit!(qualify_path_unqualified_name);
-                let path = ast::Path::cast(syntax_under_caret)?;
-                let generics = path.segment()?.generic_arg_list();
-                QualifyCandidate::UnqualifiedName(generics)
+                match ast::IdentPat::cast(syntax_under_caret.clone()) {
+                    // the bound of a range pattern can only be a constant, elsewhere the
+                    // pattern introduces a binding
+                    Some(pat) => {
+                        if !ast::RangePat::can_cast(pat.syntax().parent()?.kind()) {
+                            return None;
+                        }
+                        QualifyCandidate::UnqualifiedName(String::new())
+                    }
+                    None => {
+                        let path = ast::Path::cast(syntax_under_caret)?;
+                        QualifyCandidate::UnqualifiedName(text_after_first_name(&path)?)
+                    }
+                }
             }
             ImportCandidate::TraitAssocItem(_) => {
                 cov_mark::hit!(qualify_path_trait_assoc_item);
                 let path = ast::Path::cast(syntax_under_caret)?;
                 let (qualifier, segment) = (path.qualifier()?, path.segment()?);
+                let qualifier = match ctx.sema.resolve_path(&qualifier) {
+                    // `Self` can't be used in the qualified path, spell out the implementing type
+                    Some(PathResolution::SelfType(impl_)) => {
+                        ctx.sema.source(impl_)?.value.self_ty()?
+                    }
+                    _ => make::ty_path(qualifier),
+                };
                 QualifyCandidate::TraitAssocItem(qualifier, segment)
             }
             ImportCandidate::TraitMethod(_) => {
                 cov_mark::hit!(qualify_path_trait_method);
                 let mcall_expr = ast::MethodCallExpr::cast(syntax_under_caret)?;
-                QualifyCandidate::TraitMethod(ctx.sema.db, mcall_expr)
+                // a call put together by a macro, like `$recv.$method()`, isn't written out
+                // anywhere, rewriting it would replace the whole macro call
+                if ctx.sema.original_range_opt(mcall_expr.syntax()).is_none() {
+                    cov_mark::hit!(qualify_path_trait_method_assembled_by_macro);
+                    return None;
+                }
+                let receiver_ty = ctx.sema.type_of_expr(&mcall_expr.receiver()?)?.original;
+                QualifyCandidate::TraitMethod(ctx.sema.db, mcall_expr, receiver_ty)
             }
         },
         // derive attribute path
-        NodeOrToken::Token(_) => QualifyCandidate::UnqualifiedName(None),
+        NodeOrToken::Token(_) => QualifyCandidate::UnqualifiedName(String::new()),
     };
+    Some(qualify_candidate)
+}
 
-    // we aren't interested in different namespaces
-    proposed_imports.dedup_by(|a, b| a.import_path == b.import_path);
-
-    let group_label = group_label(candidate);
-    for import in proposed_imports {
-        acc.add_group(
-            &group_label,
-            AssistId("qualify_path", AssistKind::QuickFix),
-            label(candidate, &import),
-            range,
-            |builder| {
-                qualify_candidate.qualify(
-                    |replace_with: String| builder.replace(range, replace_with),
-                    &import.import_path,
-                    import.item_to_import,
-                )
-            },
-        );
-    }
-    Some(())
+/// The text of `path` after the name of its first segment, kept as written so that the edit
+/// doesn't reformat code rustfmt may be told to skip.
+fn text_after_first_name(path: &ast::Path) -> Option<String> {
+    let name = path.first_segment()?.name_ref()?;
+    let start = name.syntax().text_range().end() - path.syntax().text_range().start();
+    Some(path.syntax().text().slice(start..).to_string())
 }
+
 pub(crate) enum QualifyCandidate<'db> {
-    QualifierStart(ast::PathSegment, Option<ast::GenericArgList>),
-    UnqualifiedName(Option<ast::GenericArgList>),
-    TraitAssocItem(ast::Path, ast::PathSegment),
-    TraitMethod(&'db RootDatabase, ast::MethodCallExpr),
+    /// The rest of the path after the unresolved first segment's name.
+    QualifierStart(String),
+    /// The generic arguments after the unresolved name.
+    UnqualifiedName(String),
+    TraitAssocItem(ast::Type, ast::PathSegment),
+    TraitMethod(&'db RootDatabase, ast::MethodCallExpr, hir::Type),
     ImplMethod(&'db RootDatabase, ast::MethodCallExpr, hir::Function),
 }
 
 impl QualifyCandidate<'_> {
+    /// Whether qualifying with `import` would leave the path as it is, like `Foo` for `Foo`.
+    fn is_noop(&self, import: &hir::ModPath) -> bool {
+        match self {
+            // the path to the item ends with the name the path starts with
+            QualifyCandidate::QualifierStart(_) | QualifyCandidate::UnqualifiedName(_) => {
+                import.kind == hir::PathKind::Plain && import.len() == 1
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn qualify(
         &self,
         mut replacer: impl FnMut(String),
@@ -116,44 +888,51 @@ impl QualifyCandidate<'_> {
     ) {
         let import = mod_path_to_ast(import);
         match self {
-            QualifyCandidate::QualifierStart(segment, generics) => {
-                let generics = generics.as_ref().map_or_else(String::new, ToString::to_string);
-                replacer(format!("{import}{generics}::{segment}"));
-            }
-            QualifyCandidate::UnqualifiedName(generics) => {
-                let generics = generics.as_ref().map_or_else(String::new, ToString::to_string);
-                replacer(format!("{import}{generics}"));
+            QualifyCandidate::QualifierStart(rest) | QualifyCandidate::UnqualifiedName(rest) => {
+                replacer(format!("{import}{rest}"));
             }
             QualifyCandidate::TraitAssocItem(qualifier, segment) => {
                 replacer(format!("<{qualifier} as {import}>::{segment}"));
             }
-            QualifyCandidate::TraitMethod(db, mcall_expr) => {
-                Self::qualify_trait_method(db, mcall_expr, replacer, import, item);
+            QualifyCandidate::TraitMethod(db, mcall_expr, receiver_ty) => {
+                Self::qualify_trait_method(db, mcall_expr, receiver_ty, replacer, import, item);
             }
             QualifyCandidate::ImplMethod(db, mcall_expr, hir_fn) => {
-                Self::qualify_fn_call(db, mcall_expr, replacer, import, hir_fn);
+                Self::qualify_fn_call(db, mcall_expr, None, replacer, import, hir_fn);
             }
         }
     }
 
+    /// `receiver_adjustment` is the type of the receiver along with the number of
+    /// dereferences needed to get to the type the method is implemented for.
     fn qualify_fn_call(
         db: &RootDatabase,
         mcall_expr: &ast::MethodCallExpr,
+        receiver_adjustment: Option<(&hir::Type, usize)>,
         mut replacer: impl FnMut(String),
         import: ast::Path,
         hir_fn: &hir::Function,
     ) -> Option<()> {
-        let receiver = mcall_expr.receiver()?;
+        // the receiver becomes an argument, which needs no parentheses of its own
+        let mut receiver = mcall_expr.receiver()?;
+        while let ast::Expr::ParenExpr(paren) = &receiver {
+            receiver = paren.expr()?;
+        }
         let method_name = mcall_expr.name_ref()?;
         let generics =
             mcall_expr.generic_arg_list().as_ref().map_or_else(String::new, ToString::to_string);
         let arg_list = mcall_expr.arg_list().map(|arg_list| arg_list.args());
 
         if let Some(self_access) = hir_fn.self_param(db).map(|sp| sp.access(db)) {
-            let receiver = match self_access {
-                hir::Access::Shared => make::expr_ref(receiver, false),
-                hir::Access::Exclusive => make::expr_ref(receiver, true),
-                hir::Access::Owned => receiver,
+            let receiver = match receiver_adjustment {
+                Some((receiver_ty, derefs)) => {
+                    adjust_receiver(receiver, receiver_ty, derefs, self_access)
+                }
+                None => match self_access {
+                    hir::Access::Shared => make::expr_ref(prefix_operand(receiver), false),
+                    hir::Access::Exclusive => make::expr_ref(prefix_operand(receiver), true),
+                    hir::Access::Owned => receiver,
+                },
             };
             let arg_list = match arg_list {
                 Some(args) => make::arg_list(iter::once(receiver).chain(args)),
@@ -167,6 +946,7 @@ impl QualifyCandidate<'_> {
     fn qualify_trait_method(
         db: &RootDatabase,
         mcall_expr: &ast::MethodCallExpr,
+        receiver_ty: &hir::Type,
         replacer: impl FnMut(String),
         import: ast::Path,
         item: hir::ItemInNs,
@@ -174,7 +954,56 @@ impl QualifyCandidate<'_> {
         let trait_method_name = mcall_expr.name_ref()?;
         let trait_ = item_as_trait(db, item)?;
         let method = find_trait_method(db, trait_, &trait_method_name)?;
-        Self::qualify_fn_call(db, mcall_expr, replacer, import, &method)
+        // Method calls autoderef their receiver, the qualified call has to do it explicitly.
+        let derefs = receiver_ty
+            .autoderef(db)
+            .position(|ty| ty.impls_trait_with_any_args(db, trait_))
+            .unwrap_or(0);
+        Self::qualify_fn_call(
+            db,
+            mcall_expr,
+            Some((receiver_ty, derefs)),
+            replacer,
+            import,
+            &method,
+        )
+    }
+}
+
+fn adjust_receiver(
+    receiver: ast::Expr,
+    receiver_ty: &hir::Type,
+    derefs: usize,
+    self_access: hir::Access,
+) -> ast::Expr {
+    // A reference receiver can be passed as is if it is the reference the method expects.
+    if derefs == 1 {
+        match (receiver_ty.as_reference(), self_access) {
+            (Some((_, hir::Mutability::Shared)), hir::Access::Shared)
+            | (Some((_, hir::Mutability::Mut)), hir::Access::Exclusive) => return receiver,
+            _ => (),
+        }
+    }
+    let receiver = (0..derefs)
+        .fold(receiver, |receiver, _| make::expr_prefix(T![*], prefix_operand(receiver)));
+    match self_access {
+        hir::Access::Shared => make::expr_ref(prefix_operand(receiver), false),
+        hir::Access::Exclusive => make::expr_ref(prefix_operand(receiver), true),
+        hir::Access::Owned => receiver,
+    }
+}
+
+/// Parenthesizes `operand` if it binds looser than a prefix operator, like `a + b` in `&(a + b)`.
+fn prefix_operand(operand: ast::Expr) -> ast::Expr {
+    match operand {
+        ast::Expr::BinExpr(_)
+        | ast::Expr::CastExpr(_)
+        | ast::Expr::RangeExpr(_)
+        | ast::Expr::ClosureExpr(_)
+        | ast::Expr::ReturnExpr(_)
+        | ast::Expr::BreakExpr(_)
+        | ast::Expr::YieldExpr(_) => make::expr_paren(operand),
+        _ => operand,
     }
 }
 
@@ -205,7 +1034,7 @@ fn item_as_trait(db: &RootDatabase, item: hir::ItemInNs) -> Option<hir::Trait> {
     }
 }
 
-fn group_label(candidate: &ImportCandidate) -> GroupLabel {
+fn group_label(candidate: &ImportCandidate, builtin: bool) -> GroupLabel {
     let name = match candidate {
         ImportCandidate::Path(it) => &it.name,
         ImportCandidate::TraitAssocItem(it) | ImportCandidate::TraitMethod(it) => {
@@ -213,7 +1042,25 @@ fn group_label(candidate: &ImportCandidate) -> GroupLabel {
         }
     }
     .text();
-    GroupLabel(format!("Qualify {name}"))
+    if builtin {
+        GroupLabel(format!("Qualify {name} from the standard library"))
+    } else {
+        GroupLabel(format!("Qualify {name}"))
+    }
+}
+
+/// Codes of the rustc errors for a path which does not resolve: cannot find a trait, type, struct,
+/// value or pattern, and failed to resolve a path.
+const UNRESOLVED_PATH_CODES: &[&str] =
+    &["E0405", "E0412", "E0422", "E0423", "E0425", "E0433", "E0531", "E0532"];
+
+/// Codes of the compiler diagnostics which qualifying the candidate fixes.
+fn fixed_diagnostics(candidate: &ImportCandidate) -> &'static [&'static str] {
+    match candidate {
+        ImportCandidate::Path(_) => UNRESOLVED_PATH_CODES,
+        // no method or associated item found for a type, as its trait is not in scope
+        ImportCandidate::TraitAssocItem(_) | ImportCandidate::TraitMethod(_) => &["E0599"],
+    }
 }
 
 fn label(candidate: &ImportCandidate, import: &LocatedImport) -> String {
@@ -229,7 +1076,21 @@ fn label(candidate: &ImportCandidate, import: &LocatedImport) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable, check_assist_target};
+    use std::sync::{Arc, Mutex};
+
+    use expect_test::expect;
+    use ide_db::{
+        base_db::{fixture::WithFixture, FileRange},
+        imports::insert_use::InsertUseConfig,
+    };
+
+    use crate::{AssistConfig, AssistResolveStrategy};
+
+    use crate::tests::{
+        check_assist, check_assist_by_label, check_assist_labels, check_assist_labels_with_config,
+        check_assist_not_applicable, check_assist_not_applicable_with_config, check_assist_target,
+        check_assist_with_all_candidates, check_assist_with_config, TEST_CONFIG,
+    };
 
     use super::*;
 
@@ -312,35 +1173,152 @@ pub mod PubMod {
     }
 
     #[test]
-    fn applicable_when_found_multiple_imports() {
+    fn macro_multiple_args() {
         check_assist(
             qualify_path,
             r#"
-PubSt$0ruct
-
-pub mod PubMod1 {
-    pub struct PubStruct;
-}
-pub mod PubMod2 {
-    pub struct PubStruct;
+macro_rules! foo {
+    ($a:ident, $b:ident) => { fn foo(a: $a, b: $b) {} }
 }
-pub mod PubMod3 {
+foo!(PubStruct, Other$0Struct);
+
+pub mod PubMod {
     pub struct PubStruct;
+    pub struct OtherStruct;
 }
 "#,
             r#"
-PubMod3::PubStruct
+macro_rules! foo {
+    ($a:ident, $b:ident) => { fn foo(a: $a, b: $b) {} }
+}
+foo!(PubStruct, PubMod::OtherStruct);
 
-pub mod PubMod1 {
+pub mod PubMod {
     pub struct PubStruct;
+    pub struct OtherStruct;
 }
-pub mod PubMod2 {
+"#,
+        );
+    }
+
+    #[test]
+    fn all_candidates_of_multiple_imports() {
+        check_assist_with_all_candidates(
+            qualify_path,
+            r#"
+PubSt$0ruct
+
+pub mod PubMod1 {
+    pub struct PubStruct;
+}
+pub mod PubMod2 {
+    pub struct PubStruct;
+}
+pub mod PubMod3 {
+    pub struct PubStruct;
+}
+"#,
+            expect![[r#"
+                // Qualify as `PubMod1::PubStruct`
+                PubMod1::PubStruct
+
+                pub mod PubMod1 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod2 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod3 {
+                    pub struct PubStruct;
+                }
+                // Qualify as `PubMod2::PubStruct`
+                PubMod2::PubStruct
+
+                pub mod PubMod1 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod2 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod3 {
+                    pub struct PubStruct;
+                }
+                // Qualify as `PubMod3::PubStruct`
+                PubMod3::PubStruct
+
+                pub mod PubMod1 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod2 {
+                    pub struct PubStruct;
+                }
+                pub mod PubMod3 {
+                    pub struct PubStruct;
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn applicable_when_found_multiple_imports() {
+        check_assist(
+            qualify_path,
+            r#"
+PubSt$0ruct
+
+pub mod PubMod1 {
+    pub struct PubStruct;
+}
+pub mod PubMod2 {
+    pub struct PubStruct;
+}
+pub mod PubMod3 {
+    pub struct PubStruct;
+}
+"#,
+            r#"
+PubMod3::PubStruct
+
+pub mod PubMod1 {
+    pub struct PubStruct;
+}
+pub mod PubMod2 {
+    pub struct PubStruct;
+}
+pub mod PubMod3 {
+    pub struct PubStruct;
+}
+"#,
+        );
+        check_assist_by_label(
+            qualify_path,
+            r#"
+PubSt$0ruct
+
+pub mod PubMod1 {
+    pub struct PubStruct;
+}
+pub mod PubMod2 {
     pub struct PubStruct;
 }
 pub mod PubMod3 {
     pub struct PubStruct;
 }
 "#,
+            r#"
+PubMod2::PubStruct
+
+pub mod PubMod1 {
+    pub struct PubStruct;
+}
+pub mod PubMod2 {
+    pub struct PubStruct;
+}
+pub mod PubMod3 {
+    pub struct PubStruct;
+}
+"#,
+            "Qualify as `PubMod2::PubStruct`",
         );
     }
 
@@ -379,6 +1357,78 @@ pub mod PubMod {
         check_assist_not_applicable(qualify_path, r#"PubStruct$0"#);
     }
 
+    #[test]
+    fn logs_search_without_imports_found() {
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_test_writer()
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            check_assist_not_applicable(qualify_path, r#"PubStruct$0"#);
+        });
+    }
+
+    #[test]
+    fn logs_search_timing_at_trace_level() {
+        #[derive(Clone, Default)]
+        struct Records(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Records {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let logged = |level| {
+            let records = Records::default();
+            let subscriber = tracing_subscriber::fmt()
+                .with_max_level(level)
+                .with_writer({
+                    let records = records.clone();
+                    move || records.clone()
+                })
+                .finish();
+            tracing::subscriber::with_default(subscriber, || {
+                check_assist(
+                    qualify_path,
+                    r"
+mod foo { pub struct Foo; }
+fn main() { Foo$0; }
+",
+                    r"
+mod foo { pub struct Foo; }
+fn main() { foo::Foo; }
+",
+                );
+            });
+            let records = records.0.lock().unwrap();
+            String::from_utf8(records.clone()).unwrap()
+        };
+
+        let search_records = |records: &str| {
+            records
+                .lines()
+                .filter(|it| it.contains("qualify_path: searched for paths"))
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+
+        let records = search_records(&logged(tracing::Level::TRACE));
+        assert_eq!(records.len(), 1, "{records:?}");
+        assert!(records[0].contains("candidates=1"), "{records:?}");
+        assert!(records[0].contains("elapsed_us="), "{records:?}");
+
+        let records = search_records(&logged(tracing::Level::DEBUG));
+        assert_eq!(records.len(), 1, "{records:?}");
+        assert!(records[0].contains("candidates=1"), "{records:?}");
+        assert!(!records[0].contains("elapsed_us="), "{records:?}");
+    }
+
     #[test]
     fn qualify_function() {
         check_assist(
@@ -439,6 +1489,88 @@ mod m { pub struct GroupLabel; }
         )
     }
 
+    #[test]
+    fn qualify_path_qualifier_start_target() {
+        check_assist_target(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub struct TestStruct {}
+    impl TestStruct {
+        const TEST_CONST: u8 = 42;
+    }
+}
+
+fn main() {
+    TestStruct::TEST_CONST$0
+}
+"#,
+            "TestStruct",
+        )
+    }
+
+    #[test]
+    fn qualify_path_trait_method_target() {
+        check_assist_target(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_struct.test_meth$0od()
+}
+"#,
+            "test_method",
+        )
+    }
+
+    #[test]
+    fn trait_methods_ordered_by_module_distance() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub mod b {
+        pub mod c {
+            pub trait Far {
+                fn method(&self) {}
+            }
+            impl Far for crate::Foo {}
+        }
+    }
+}
+
+pub struct Foo;
+
+mod app {
+    pub mod ext {
+        pub trait Near {
+            fn method(&self) {}
+        }
+        impl Near for crate::Foo {}
+    }
+
+    fn main() {
+        crate::Foo.meth$0od();
+    }
+}
+"#,
+            expect![[r#"
+                Qualify method: Qualify with `ext::Near`
+                Qualify method: Qualify with `crate::a::b::c::Far`
+            "#]],
+        );
+    }
+
     #[test]
     fn not_applicable_when_path_start_is_imported() {
         check_assist_not_applicable(
@@ -739,6 +1871,31 @@ fn main() {
         );
     }
 
+    #[test]
+    fn trait_method_disabled() {
+        cov_mark::check!(qualify_path_trait_methods_disabled);
+        check_assist_not_applicable_with_config(
+            qualify_path,
+            AssistConfig { qualify_trait_methods: false, ..TEST_CONFIG },
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_struct.test_meth$0od()
+}
+"#,
+        );
+    }
+
     #[test]
     fn trait_method_multi_params() {
         check_assist(
@@ -817,6 +1974,93 @@ fn main() {
         );
     }
 
+    #[test]
+    fn trait_method_reference_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+fn main(test_struct: &test_mod::TestStruct) {
+    test_struct.test_meth$0od()
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+fn main(test_struct: &test_mod::TestStruct) {
+    test_mod::TestTrait::test_method(test_struct)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_deref_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+//- minicore: deref
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+struct Wrapper(test_mod::TestStruct);
+impl core::ops::Deref for Wrapper {
+    type Target = test_mod::TestStruct;
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+fn main(wrapper: &Wrapper) {
+    wrapper.test_meth$0od()
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+struct Wrapper(test_mod::TestStruct);
+impl core::ops::Deref for Wrapper {
+    type Target = test_mod::TestStruct;
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+fn main(wrapper: &Wrapper) {
+    test_mod::TestTrait::test_method(&**wrapper)
+}
+"#,
+        );
+    }
+
     #[test]
     fn trait_method_cross_crate() {
         check_assist(
@@ -1082,6 +2326,29 @@ fn main() {
         );
     }
 
+    #[test]
+    fn exact_case_is_offered_exclusively() {
+        check_assist_labels(
+            qualify_path,
+            r"
+//- /lib.rs crate:dep
+pub mod upper { pub struct FMT; }
+pub mod camel { pub struct Fmt; }
+pub mod lower { pub struct fmt; }
+pub mod other { pub struct Fmt; }
+
+//- /main.rs crate:main deps:dep
+fn main() {
+    Fmt$0;
+}
+",
+            expect![[r#"
+                Qualify Fmt: Qualify as `dep::camel::Fmt`
+                Qualify Fmt: Qualify as `dep::other::Fmt`
+            "#]],
+        );
+    }
+
     #[test]
     fn keep_generic_annotations() {
         check_assist(
@@ -1125,10 +2392,58 @@ fn main() {}
     }
 
     #[test]
-    fn associated_struct_const_generic() {
+    fn keep_const_generic_annotations() {
         check_assist(
             qualify_path,
-            r#"
+            r"
+//- /lib.rs crate:dep
+pub mod buffer { pub struct Buffer<const N: usize>([u8; N]); }
+
+//- /main.rs crate:main deps:dep
+fn foo() -> Buff$0er<{1 + 1}> {}
+",
+            r"
+fn foo() -> dep::buffer::Buffer<{1 + 1}> {}
+",
+        );
+    }
+
+    #[test]
+    fn associated_struct_const_generic_braced_arg() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub struct Buffer<const N: usize> {}
+    impl<const N: usize> Buffer<N> {
+        const LEN: usize = N;
+    }
+}
+
+fn main() {
+    Buffer::<{ 1 + 1 }>::LEN$0
+}
+"#,
+            r#"
+mod test_mod {
+    pub struct Buffer<const N: usize> {}
+    impl<const N: usize> Buffer<N> {
+        const LEN: usize = N;
+    }
+}
+
+fn main() {
+    test_mod::Buffer::<{ 1 + 1 }>::LEN
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn associated_struct_const_generic() {
+        check_assist(
+            qualify_path,
+            r#"
 mod test_mod {
     pub struct TestStruct<T> {}
     impl<T> TestStruct<T> {
@@ -1231,6 +2546,45 @@ fn main() {
         );
     }
 
+    #[test]
+    fn trait_method_turbofish_with_args() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method<T, U>(&self, t: T, u: U);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method<T, U>(&self, t: T, u: U) {}
+    }
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_struct.test_meth$0od::<u8, _>(42, ())
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method<T, U>(&self, t: T, u: U);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method<T, U>(&self, t: T, u: U) {}
+    }
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_method::<u8, _>(&test_struct, 42, ())
+}
+"#,
+        );
+    }
+
     #[test]
     fn works_in_derives() {
         check_assist(
@@ -1292,4 +2646,3578 @@ use foo::Foo$0;
 ",
         );
     }
+
+    #[test]
+    fn qualify_all_unambiguous_paths() {
+        check_assist(
+            qualify_all_paths,
+            r#"
+mod foo {
+    pub struct Foo;
+    pub struct Baz;
+}
+mod bar {
+    pub struct Bar;
+    pub struct Baz;
+}
+
+fn main() {
+    let _ = Fo$0o;
+    let _ = Bar;
+    let _ = Baz;
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+    pub struct Baz;
+}
+mod bar {
+    pub struct Bar;
+    pub struct Baz;
+}
+
+fn main() {
+    let _ = foo::Foo;
+    let _ = bar::Bar;
+    let _ = Baz;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_all_paths_ignores_alias_of_candidate() {
+        check_assist(
+            qualify_all_paths,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub type Foo = crate::a::Foo;
+}
+
+fn f(_: Fo$0o) {}
+"#,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub type Foo = crate::a::Foo;
+}
+
+fn f(_: a::Foo) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_all_paths_looks_up_a_limited_number_of_paths() {
+        cov_mark::check!(qualify_all_paths_capped);
+        let paths = |qualified: usize, unqualified: usize| {
+            "foo::Foo;\n".repeat(qualified) + &"Foo;\n".repeat(unqualified)
+        };
+        let before = format!(
+            "mod foo {{ pub struct Foo; }}\nfn main() {{\nFoo$0;\n{}}}\n",
+            paths(0, MAX_UNRESOLVED_PATHS)
+        );
+        let after = format!(
+            "mod foo {{ pub struct Foo; }}\nfn main() {{\n{}}}\n",
+            paths(MAX_UNRESOLVED_PATHS, 1)
+        );
+        check_assist(qualify_all_paths, &before, &after);
+    }
+
+    #[test]
+    fn qualify_all_paths_not_applicable_on_resolved_path() {
+        check_assist_not_applicable(
+            qualify_all_paths,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+use foo::Foo;
+
+fn main() {
+    let _ = Fo$0o;
+    let _ = Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_terminal_segment_without_trailing_colons() {
+        check_assist(
+            qualify_path,
+            r#"
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = HashMap$0;
+}
+"#,
+            r#"
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = std::collections::HashMap;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_path_with_trailing_colons() {
+        check_assist(
+            qualify_path,
+            r#"
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = HashMap::$0;
+}
+"#,
+            r#"
+mod std { pub mod collections { pub struct HashMap; } }
+fn main() {
+    let _ = std::collections::HashMap::;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_assoc_item_on_self() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn helper();
+    }
+    impl<T> TestTrait for super::TestStruct<T> {
+        fn helper() {}
+    }
+}
+
+struct TestStruct<T>(T);
+
+impl<T> TestStruct<T> {
+    fn call() {
+        Self::helper$0();
+    }
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn helper();
+    }
+    impl<T> TestTrait for super::TestStruct<T> {
+        fn helper() {}
+    }
+}
+
+struct TestStruct<T>(T);
+
+impl<T> TestStruct<T> {
+    fn call() {
+        <TestStruct<T> as test_mod::TestTrait>::helper();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_public_item_in_private_module() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod outer {
+    mod private {
+        pub struct S;
+    }
+}
+fn main() {
+    S$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_through_reexport_of_private_module_item() {
+        check_assist(
+            qualify_path,
+            r#"
+mod outer {
+    mod private {
+        pub struct S;
+    }
+    pub use self::private::S;
+}
+fn main() {
+    S$0;
+}
+"#,
+            r#"
+mod outer {
+    mod private {
+        pub struct S;
+    }
+    pub use self::private::S;
+}
+fn main() {
+    outer::S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_with_leading_colons_when_crate_is_shadowed() {
+        check_assist_by_label(
+            qualify_path,
+            r#"
+//- /lib.rs crate:dep
+pub struct Struct;
+
+//- /main.rs crate:main deps:dep
+mod dep {}
+
+fn main() {
+    Struct$0;
+}
+"#,
+            r#"
+mod dep {}
+
+fn main() {
+    ::dep::Struct;
+}
+"#,
+            "Qualify as `::dep::Struct`",
+        );
+    }
+
+    #[test]
+    fn qualify_with_extern_crate_name() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /lib.rs crate:dep
+pub struct Struct;
+
+//- /main.rs crate:main deps:dep
+fn main() {
+    Struct$0;
+}
+"#,
+            r#"
+fn main() {
+    dep::Struct;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_path_caret_on_separator() {
+        check_assist(
+            qualify_path,
+            r#"
+mod std { pub mod collections { pub struct HashMap; impl HashMap { pub fn new() {} } } }
+fn main() {
+    HashMap:$0:new();
+}
+"#,
+            r#"
+mod std { pub mod collections { pub struct HashMap; impl HashMap { pub fn new() {} } } }
+fn main() {
+    std::collections::HashMap::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_path_caret_after_separator() {
+        check_assist(
+            qualify_path,
+            r#"
+mod std { pub mod collections { pub struct HashMap; impl HashMap { pub fn new() {} } } }
+fn main() {
+    HashMap::$0new();
+}
+"#,
+            r#"
+mod std { pub mod collections { pub struct HashMap; impl HashMap { pub fn new() {} } } }
+fn main() {
+    std::collections::HashMap::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn builtin_candidates_are_grouped_separately() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:alloc
+#![no_std]
+mod local {
+    pub struct Vec;
+}
+
+fn main() {
+    Vec$0;
+}
+//- /alloc.rs crate:alloc
+pub mod vec {
+    pub struct Vec;
+}
+"#,
+            expect![[r#"
+                Qualify Vec from the standard library: Qualify as `alloc::vec::Vec`
+                Qualify Vec: Qualify as `local::Vec`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn qualify_tuple_struct_constructor() {
+        check_assist(
+            qualify_path,
+            r#"
+mod PubMod {
+    pub struct MyTuple(pub u32, pub u32);
+}
+fn main() {
+    let _ = MyTuple$0(1, 2);
+}
+"#,
+            r#"
+mod PubMod {
+    pub struct MyTuple(pub u32, pub u32);
+}
+fn main() {
+    let _ = PubMod::MyTuple(1, 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_record_literal() {
+        check_assist(
+            qualify_path,
+            r#"
+mod geo {
+    pub struct Point { pub x: u32 }
+}
+fn main() {
+    let _ = Point$0 { x: 1 };
+}
+"#,
+            r#"
+mod geo {
+    pub struct Point { pub x: u32 }
+}
+fn main() {
+    let _ = geo::Point { x: 1 };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_ambiguous_glob_import() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub struct Foo;
+}
+use a::*;
+use b::*;
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `a::Foo`
+                Qualify Foo: Qualify as `b::Foo`
+            "#]],
+        );
+        check_assist_by_label(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub struct Foo;
+}
+use a::*;
+use b::*;
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub struct Foo;
+}
+use a::*;
+use b::*;
+
+fn main() {
+    let _ = b::Foo;
+}
+"#,
+            "Qualify as `b::Foo`",
+        );
+    }
+
+    #[test]
+    fn qualify_ambiguous_nested_glob_import() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod m {
+    pub mod a {
+        pub struct Foo;
+    }
+    pub mod b {
+        pub struct Foo;
+    }
+}
+use m::{a::*, b::*};
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `m::a::Foo`
+                Qualify Foo: Qualify as `m::b::Foo`
+            "#]],
+        );
+        check_assist_by_label(
+            qualify_path,
+            r#"
+mod m {
+    pub mod a {
+        pub struct Foo;
+    }
+    pub mod b {
+        pub struct Foo;
+    }
+}
+use m::{a::*, b::*};
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            r#"
+mod m {
+    pub mod a {
+        pub struct Foo;
+    }
+    pub mod b {
+        pub struct Foo;
+    }
+}
+use m::{a::*, b::*};
+
+fn main() {
+    let _ = m::b::Foo;
+}
+"#,
+            "Qualify as `m::b::Foo`",
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_glob_imports_in_different_namespaces() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo {}
+}
+mod b {
+    #[allow(non_snake_case)]
+    pub fn Foo() {}
+}
+use a::*;
+use b::*;
+
+fn main() {
+    Foo$0();
+}
+"#,
+        );
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo {}
+}
+mod b {
+    #[allow(non_snake_case)]
+    pub fn Foo() {}
+}
+use a::*;
+use b::*;
+
+fn f(_: Foo$0) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_ambiguous_glob_import_of_unit_struct_and_function() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    #[allow(non_snake_case)]
+    pub fn Foo() {}
+}
+use a::*;
+use b::*;
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `a::Foo`
+                Qualify Foo: Qualify as `b::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_glob_import_shadowed_by_explicit_import() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub struct Foo;
+}
+use a::*;
+use b::*;
+use a::Foo;
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_glob_reexporting_same_item() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub use crate::a::Foo;
+}
+use a::*;
+use b::*;
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_assoc_item_keeps_indentation() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        const TEST_CONST: u8;
+    }
+    impl<T> TestTrait for super::TestStruct<T> {
+        const TEST_CONST: u8 = 42;
+    }
+}
+
+struct TestStruct<T>(T);
+
+fn main() {
+    if true {
+        if true {
+            let _ = TestStruct::<
+                u8,
+            >::TEST_CONST$0;
+        }
+    }
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        const TEST_CONST: u8;
+    }
+    impl<T> TestTrait for super::TestStruct<T> {
+        const TEST_CONST: u8 = 42;
+    }
+}
+
+struct TestStruct<T>(T);
+
+fn main() {
+    if true {
+        if true {
+            let _ = <TestStruct::<
+                u8,
+            > as test_mod::TestTrait>::TEST_CONST;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_path_within_max_width() {
+        check_assist_with_config(
+            qualify_path,
+            AssistConfig { max_width: Some(21), ..TEST_CONFIG },
+            r#"
+mod foo { pub struct Bar; }
+fn main() {
+    let _ = Bar$0;
+}
+"#,
+            r#"
+mod foo { pub struct Bar; }
+fn main() {
+    let _ = foo::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_path_exceeding_max_width() {
+        cov_mark::check!(qualify_path_exceeds_max_width);
+        check_assist_not_applicable_with_config(
+            qualify_path,
+            AssistConfig { max_width: Some(20), ..TEST_CONFIG },
+            r#"
+mod foo { pub struct Bar; }
+fn main() {
+    let _ = Bar$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn max_width_ignores_ranges_outside_of_the_text() {
+        let text = "fn f() { é; }";
+        // off a char boundary, and past the end of the text
+        for range in [TextRange::new(10.into(), 11.into()), TextRange::new(20.into(), 24.into())] {
+            assert!(fits_max_width(text, range, "foo::Bar", 10));
+        }
+    }
+
+    #[test]
+    fn qualify_path_on_line_already_exceeding_max_width() {
+        check_assist_with_config(
+            qualify_path,
+            AssistConfig { max_width: Some(20), ..TEST_CONFIG },
+            r#"
+mod foo { pub struct Bar; }
+fn main() {
+    let _ = (Bar$0, 1, 2);
+}
+"#,
+            r#"
+mod foo { pub struct Bar; }
+fn main() {
+    let _ = (foo::Bar, 1, 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_tuple_variant_pattern() {
+        check_assist(
+            qualify_path,
+            r#"
+mod shapes {
+    pub enum Shape { Circle(u32), Square(u32) }
+}
+fn f(x: shapes::Shape) {
+    match x {
+        Circle$0(r) => {}
+        _ => {}
+    }
+}
+"#,
+            r#"
+mod shapes {
+    pub enum Shape { Circle(u32), Square(u32) }
+}
+fn f(x: shapes::Shape) {
+    match x {
+        shapes::Shape::Circle(r) => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_variant_pattern_matching_a_reference() {
+        check_assist(
+            qualify_path,
+            r#"
+mod shapes {
+    pub enum Shape { Circle(u32), Square(u32) }
+}
+fn f(x: &shapes::Shape) {
+    if let Square$0(s) | Circle(s) = x {}
+}
+"#,
+            r#"
+mod shapes {
+    pub enum Shape { Circle(u32), Square(u32) }
+}
+fn f(x: &shapes::Shape) {
+    if let shapes::Shape::Square(s) | Circle(s) = x {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_record_pattern() {
+        check_assist(
+            qualify_path,
+            r#"
+mod geo {
+    pub struct Point { pub x: u32 }
+}
+fn f(p: geo::Point) {
+    let Point$0 { x } = p;
+}
+"#,
+            r#"
+mod geo {
+    pub struct Point { pub x: u32 }
+}
+fn f(p: geo::Point) {
+    let geo::Point { x } = p;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_raw_identifiers() {
+        check_assist(
+            qualify_path,
+            r#"
+mod r#type {
+    pub struct Foo;
+}
+
+fn main() {
+    Foo$0;
+}
+"#,
+            r#"
+mod r#type {
+    pub struct Foo;
+}
+
+fn main() {
+    r#type::Foo;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_raw_identifiers_in_trait_method() {
+        check_assist(
+            qualify_path,
+            r#"
+mod r#type {
+    pub trait Trait {
+        fn r#match(&self) {}
+    }
+    impl Trait for () {}
+}
+
+fn main() {
+    ().r#mat$0ch();
+}
+"#,
+            r#"
+mod r#type {
+    pub trait Trait {
+        fn r#match(&self) {}
+    }
+    impl Trait for () {}
+}
+
+fn main() {
+    r#type::Trait::r#match(&());
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_generic_param_bound() {
+        check_assist(
+            qualify_path,
+            r#"
+mod fmt {
+    pub trait Display {}
+}
+
+fn f<T: Disp$0lay>(_: T) {}
+"#,
+            r#"
+mod fmt {
+    pub trait Display {}
+}
+
+fn f<T: fmt::Display>(_: T) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_where_clause_bound() {
+        check_assist(
+            qualify_path,
+            r#"
+mod fmt {
+    pub trait Display {}
+}
+
+fn f<T>(_: T)
+where
+    T: Disp$0lay,
+{
+}
+"#,
+            r#"
+mod fmt {
+    pub trait Display {}
+}
+
+fn f<T>(_: T)
+where
+    T: fmt::Display,
+{
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_extern_crate_in_2015_edition() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep edition:2015
+fn main() {
+    Struct$0;
+}
+//- /dep.rs crate:dep
+pub struct Struct;
+"#,
+            r#"
+extern crate dep;
+fn main() {
+    dep::Struct;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn labels_added_extern_crate_in_2015_edition() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep edition:2015
+fn main() {
+    Struct$0;
+}
+//- /dep.rs crate:dep
+pub struct Struct;
+"#,
+            expect![[r#"
+                Qualify Struct: Qualify as `dep::Struct` and add `extern crate dep;`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn adds_extern_crate_to_crate_root_in_2015_edition() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep edition:2015
+mod foo;
+//- /foo.rs
+fn main() {
+    Struct$0;
+}
+//- /dep.rs crate:dep
+pub struct Struct;
+"#,
+            r#"
+//- /main.rs
+extern crate dep;
+mod foo;
+//- /foo.rs
+fn main() {
+    dep::Struct;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_declared_extern_crate_in_2015_edition() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep edition:2015
+extern crate dep;
+
+fn main() {
+    Struct$0;
+}
+//- /dep.rs crate:dep
+pub struct Struct;
+"#,
+            r#"
+extern crate dep;
+
+fn main() {
+    dep::Struct;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_extern_crate_in_2018_edition() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep edition:2018
+fn main() {
+    Struct$0;
+}
+//- /dep.rs crate:dep
+pub struct Struct;
+"#,
+            r#"
+fn main() {
+    dep::Struct;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn skips_alias_of_other_candidate() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo;
+}
+mod b {
+    pub type Foo = crate::a::Foo;
+}
+
+fn main() {
+    Foo$0;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `a::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keeps_alias_of_generic_candidate() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub struct Foo<T>(T);
+}
+mod b {
+    pub type Foo = crate::a::Foo<u32>;
+}
+
+fn main() {
+    Foo$0(0);
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `a::Foo`
+                Qualify Foo: Qualify as `b::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macro_generated_name_from_macro_argument() {
+        // The item name is spelled at the macro call site, and the usage sits in a macro call.
+        check_assist(
+            qualify_path,
+            r"
+//- /lib.rs crate:dep
+macro_rules! mac {
+    ($name:ident) => {
+        pub mod inner {
+            pub struct $name;
+        }
+    };
+}
+
+mac!(Cheese);
+
+//- /main.rs crate:main deps:dep
+macro_rules! id {
+    ($($tt:tt)*) => { $($tt)* };
+}
+fn main() {
+    id!(let _ = Cheese$0;);
+}
+",
+            r"
+macro_rules! id {
+    ($($tt:tt)*) => { $($tt)* };
+}
+fn main() {
+    id!(let _ = dep::inner::Cheese;);
+}
+",
+        );
+    }
+
+    #[test]
+    fn parenthesizes_binary_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    (a + b).by_r$0ef();
+}
+"#,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    foo::Trait::by_ref(&(a + b));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn drops_parentheses_of_receiver_argument() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    (a + b).by_v$0alue();
+}
+"#,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    foo::Trait::by_value(a + b);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parenthesizes_cast_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    (a as u32).by_r$0ef();
+}
+"#,
+            r#"
+mod foo {
+    pub trait Trait {
+        fn by_ref(&self) {}
+        fn by_value(self) {}
+    }
+    impl Trait for u32 {}
+}
+
+fn main() {
+    let (a, b) = (1u32, 2u32);
+    foo::Trait::by_ref(&(a as u32));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_cast_target_type() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep
+fn main() {
+    let x = 0u32;
+    let _ = x as Int$0eger;
+}
+//- /dep.rs crate:dep
+pub type Integer = u64;
+"#,
+            r#"
+fn main() {
+    let x = 0u32;
+    let _ = x as dep::Integer;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_identical_path() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub struct TestStruct {}
+    impl TestStruct {
+        fn test_function() {}
+    }
+}
+
+fn main() {
+    test_mod::TestStruct::test_function$0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_through_reexport_of_transitive_dependency() {
+        // `dep_b` isn't a dependency of `main`, so its path must not be offered.
+        check_assist_labels(
+            qualify_path,
+            r#"
+//- /main.rs crate:main deps:dep_a
+fn main() {
+    Re$0;
+}
+//- /dep_a.rs crate:dep_a deps:dep_b
+pub use dep_b::Orig as Re;
+//- /dep_b.rs crate:dep_b
+pub struct Orig;
+"#,
+            expect![[r#"
+                Qualify Re: Qualify as `dep_a::Re`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn offers_path_from_crate_root_for_local_items() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+pub struct Foo;
+
+mod sub {
+    mod deeper {
+        pub struct Foo;
+    }
+    fn f() {
+        Foo$0;
+    }
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `deeper::Foo`
+                Qualify Foo: Qualify as `crate::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn qualify_trait_of_assoc_const_on_type_param() {
+        check_assist(
+            qualify_path,
+            r#"
+mod module {
+    pub trait Trait {
+        const CONST: usize;
+    }
+}
+
+fn f<T: module::Trait>() -> usize {
+    <T as Tra$0it>::CONST
+}
+"#,
+            r#"
+mod module {
+    pub trait Trait {
+        const CONST: usize;
+    }
+}
+
+fn f<T: module::Trait>() -> usize {
+    <T as module::Trait>::CONST
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn coalesces_paths_differing_by_crate_prefix() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod sub {
+    mod foo {
+        pub struct Bar;
+    }
+
+    fn main() {
+        Bar$0;
+    }
+}
+"#,
+            expect![[r#"
+                Qualify Bar: Qualify as `foo::Bar`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn coalesces_paths_preferring_configured_prefix() {
+        check_assist_with_config(
+            qualify_path,
+            AssistConfig {
+                insert_use: InsertUseConfig {
+                    prefix_kind: hir::PrefixKind::ByCrate,
+                    ..TEST_CONFIG.insert_use
+                },
+                ..TEST_CONFIG
+            },
+            r#"
+mod sub {
+    mod foo {
+        pub struct Bar;
+    }
+
+    fn main() {
+        Bar$0;
+    }
+}
+"#,
+            r#"
+mod sub {
+    mod foo {
+        pub struct Bar;
+    }
+
+    fn main() {
+        crate::sub::foo::Bar;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_const_in_const_generic_arg() {
+        check_assist(
+            qualify_path,
+            r#"
+mod module {
+    pub const BAR: usize = 3;
+}
+struct Foo<const N: usize>;
+
+fn main() {
+    let _: Foo<{ BA$0R }> = Foo;
+}
+"#,
+            r#"
+mod module {
+    pub const BAR: usize = 3;
+}
+struct Foo<const N: usize>;
+
+fn main() {
+    let _: Foo<{ module::BAR }> = Foo;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_attributes_of_item_with_qualified_field() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+/// Docs of `Wrapper`.
+#[derive(Clone)]
+#[allow(dead_code)]
+struct Wrapper {
+    /// Docs of the field.
+    #[allow(unused)]
+    field: Bar$0,
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+/// Docs of `Wrapper`.
+#[derive(Clone)]
+#[allow(dead_code)]
+struct Wrapper {
+    /// Docs of the field.
+    #[allow(unused)]
+    field: foo::Bar,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_attributes_of_fn_with_qualified_return_type() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+/// Docs of `make`.
+#[inline]
+fn make() -> Bar$0 {
+    loop {}
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+/// Docs of `make`.
+#[inline]
+fn make() -> foo::Bar {
+    loop {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_attributes_of_qualified_statement() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub fn bar() {}
+}
+
+fn main() {
+    #[allow(unused)]
+    // comment before the call
+    bar$0();
+}
+"#,
+            r#"
+mod foo {
+    pub fn bar() {}
+}
+
+fn main() {
+    #[allow(unused)]
+    // comment before the call
+    foo::bar();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_format_string_capture() {
+        // captures can only be plain identifiers, a qualified path would not be a valid format
+        // string anymore
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+macro_rules! format_args {
+    ($lit:literal $(tt:tt)*) => { 0 },
+}
+macro_rules! print {
+    ($($arg:tt)*) => (format_args!($($arg)*));
+}
+mod foo {
+    pub const BAR: u32 = 0;
+}
+
+fn main() {
+    print!("{BAR$0}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_with_lifetime() {
+        check_assist(
+            qualify_path,
+            r#"
+mod borrow {
+    pub enum Cow<'a, B: ?Sized> {
+        Borrowed(&'a B),
+    }
+}
+
+fn f<'a>(s: Cow$0<'a, str>) {}
+"#,
+            r#"
+mod borrow {
+    pub enum Cow<'a, B: ?Sized> {
+        Borrowed(&'a B),
+    }
+}
+
+fn f<'a>(s: borrow::Cow<'a, str>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn equally_close_traits_are_ordered_by_path() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod zed {
+    pub trait Trait {
+        fn method(&self) {}
+    }
+    impl Trait for crate::Foo {}
+}
+
+mod alpha {
+    pub trait Trait {
+        fn method(&self) {}
+    }
+    impl Trait for crate::Foo {}
+}
+
+pub struct Foo;
+
+fn main() {
+    Foo.meth$0od();
+}
+"#,
+            expect![[r#"
+                Qualify method: Qualify with `alpha::Trait`
+                Qualify method: Qualify with `zed::Trait`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trait_method_with_method_chain_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+struct Builder;
+impl Builder {
+    fn build(self) -> test_mod::TestStruct {
+        test_mod::TestStruct {}
+    }
+}
+fn builder() -> Builder {
+    Builder
+}
+
+fn main() {
+    builder().build().test_meth$0od()
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+
+struct Builder;
+impl Builder {
+    fn build(self) -> test_mod::TestStruct {
+        test_mod::TestStruct {}
+    }
+}
+fn builder() -> Builder {
+    Builder
+}
+
+fn main() {
+    test_mod::TestTrait::test_method(&builder().build())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_trait_in_impl_header() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {}
+}
+
+struct S;
+
+impl TestTra$0it for S {}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {}
+}
+
+struct S;
+
+impl test_mod::TestTrait for S {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_type_in_call_turbofish() {
+        check_assist(
+            qualify_path,
+            r#"
+mod module {
+    pub struct Bar;
+}
+
+fn foo<T>() {}
+
+fn main() {
+    foo::<Ba$0r>();
+}
+"#,
+            r#"
+mod module {
+    pub struct Bar;
+}
+
+fn foo<T>() {}
+
+fn main() {
+    foo::<module::Bar>();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn resolved_path_skips_search() {
+        cov_mark::check!(import_assets_resolved_path);
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar;
+
+fn main() {
+    let _ = Ba$0r;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_dyn_trait() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /lib.rs crate:dep
+pub mod fmt {
+    pub trait Display {}
+}
+//- /main.rs crate:main deps:dep
+struct Box<T: ?Sized>(T);
+
+fn f(_: Box<dyn Disp$0lay>) {}
+"#,
+            r#"
+struct Box<T: ?Sized>(T);
+
+fn f(_: Box<dyn dep::fmt::Display>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_all_paths_through_imported_module() {
+        check_assist(
+            qualify_all_paths_through_imported_modules,
+            r#"
+mod std {
+    pub mod fmt {
+        pub struct Formatter;
+        pub trait Write {}
+    }
+    pub mod io {
+        pub trait Write {}
+    }
+}
+use std::fmt;
+
+mod local {
+    pub struct Formatter;
+}
+
+fn write(_: &mut Format$0ter, _: &dyn Write) {}
+"#,
+            r#"
+mod std {
+    pub mod fmt {
+        pub struct Formatter;
+        pub trait Write {}
+    }
+    pub mod io {
+        pub trait Write {}
+    }
+}
+use std::fmt;
+
+mod local {
+    pub struct Formatter;
+}
+
+fn write(_: &mut fmt::Formatter, _: &dyn fmt::Write) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_all_paths_through_imported_modules_honors_max_width() {
+        check_assist_with_config(
+            qualify_all_paths_through_imported_modules,
+            AssistConfig { max_width: Some(33), ..TEST_CONFIG },
+            r#"
+mod std {
+    pub mod fmt {
+        pub struct Formatter;
+        pub trait Write {}
+    }
+}
+use std::fmt;
+
+fn f(_: &mut Format$0ter) {}
+fn g(_: &dyn Write, _: u32) {}
+"#,
+            r#"
+mod std {
+    pub mod fmt {
+        pub struct Formatter;
+        pub trait Write {}
+    }
+}
+use std::fmt;
+
+fn f(_: &mut fmt::Formatter) {}
+fn g(_: &dyn Write, _: u32) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_all_paths_through_imported_modules_not_applicable_without_import() {
+        check_assist_not_applicable(
+            qualify_all_paths_through_imported_modules,
+            r#"
+mod fmt {
+    pub struct Formatter;
+}
+
+fn write(_: &mut Format$0ter) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_keeps_try_operator() {
+        check_assist(
+            qualify_path,
+            r#"
+//- minicore: option, try
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self) -> Option<u32>;
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) -> Option<u32> {
+            None
+        }
+    }
+}
+
+fn main() -> Option<u32> {
+    let test_struct = test_mod::TestStruct {};
+    test_struct.test_meth$0od()?
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self) -> Option<u32>;
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) -> Option<u32> {
+            None
+        }
+    }
+}
+
+fn main() -> Option<u32> {
+    let test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_method(&test_struct)?
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_keeps_await() {
+        check_assist(
+            qualify_path,
+            r#"
+//- minicore: future
+mod test_mod {
+    use core::{future::Future, pin::Pin, task::{Context, Poll}};
+
+    pub struct Done;
+    impl Future for Done {
+        type Output = ();
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            Poll::Ready(())
+        }
+    }
+    pub trait TestTrait {
+        fn test_method(&self) -> Done;
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) -> Done {
+            Done
+        }
+    }
+}
+
+async fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_struct.test_meth$0od().await
+}
+"#,
+            r#"
+mod test_mod {
+    use core::{future::Future, pin::Pin, task::{Context, Poll}};
+
+    pub struct Done;
+    impl Future for Done {
+        type Output = ();
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            Poll::Ready(())
+        }
+    }
+    pub trait TestTrait {
+        fn test_method(&self) -> Done;
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) -> Done {
+            Done
+        }
+    }
+}
+
+async fn main() {
+    let test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_method(&test_struct).await
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn item_reexported_twice_is_offered_once() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+//- /lib.rs crate:dep
+pub mod b {
+    pub struct Foo;
+}
+pub mod x {
+    pub use crate::b::Foo as Bar;
+}
+pub mod y {
+    pub use crate::b::Foo as Bar;
+}
+//- /main.rs crate:main deps:dep
+fn main() {
+    let _ = Bar$0;
+}
+"#,
+            expect![[r#"
+                Qualify Bar: Qualify as `dep::y::Bar`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn qualify_shortest_path_only() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod a {
+    pub mod b {
+        pub struct Foo;
+    }
+}
+mod c {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Fo$0o;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `a::b::Foo`
+                Qualify Foo: Qualify as `c::Foo`
+            "#]],
+        );
+        check_assist_labels_with_config(
+            qualify_path,
+            AssistConfig { qualify_shortest_path_only: true, ..TEST_CONFIG },
+            r#"
+mod a {
+    pub mod b {
+        pub struct Foo;
+    }
+}
+mod c {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Fo$0o;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `c::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn qualify_in_static_initializer() {
+        check_assist(
+            qualify_path,
+            r#"
+mod init {
+    pub const fn function() -> u32 {
+        0
+    }
+}
+
+static X: u32 = func$0tion();
+"#,
+            r#"
+mod init {
+    pub const fn function() -> u32 {
+        0
+    }
+}
+
+static X: u32 = init::function();
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_in_const_initializer() {
+        check_assist(
+            qualify_path,
+            r#"
+mod init {
+    pub struct Limits {
+        pub max: u32,
+    }
+    pub const DEFAULT_MAX: u32 = 10;
+}
+
+const LIMITS: init::Limits = init::Limits { max: DEFAULT$0_MAX };
+"#,
+            r#"
+mod init {
+    pub struct Limits {
+        pub max: u32,
+    }
+    pub const DEFAULT_MAX: u32 = 10;
+}
+
+const LIMITS: init::Limits = init::Limits { max: init::DEFAULT_MAX };
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_imported_path_start() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+pub mod mod1 {
+    pub mod mod2 {
+        pub mod mod3 {
+            pub struct TestStruct;
+        }
+    }
+}
+
+mod inner {
+    use crate::mod1::mod2;
+    fn main() {
+        mod2::mod3::TestStruct$0;
+    }
+}
+"#,
+        );
+        check_assist_with_config(
+            qualify_path,
+            AssistConfig { qualify_imported_path_start: true, ..TEST_CONFIG },
+            r#"
+pub mod mod1 {
+    pub mod mod2 {
+        pub mod mod3 {
+            pub struct TestStruct;
+        }
+    }
+}
+
+mod inner {
+    use crate::mod1::mod2;
+    fn main() {
+        mod2::mod3::TestStruct$0;
+    }
+}
+"#,
+            r#"
+pub mod mod1 {
+    pub mod mod2 {
+        pub mod mod3 {
+            pub struct TestStruct;
+        }
+    }
+}
+
+mod inner {
+    use crate::mod1::mod2;
+    fn main() {
+        crate::mod1::mod2::mod3::TestStruct;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_imported_path_start_not_applicable_for_local_module() {
+        check_assist_not_applicable_with_config(
+            qualify_path,
+            AssistConfig { qualify_imported_path_start: true, ..TEST_CONFIG },
+            r#"
+mod inner {
+    mod mod2 {
+        pub struct TestStruct;
+    }
+    fn main() {
+        mod2::TestStruct$0;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_keeps_explicit_default_generic_arg() {
+        check_assist(
+            qualify_path,
+            r#"
+mod collections {
+    pub struct RandomState;
+    pub struct HashMap<K, V, S = RandomState>(K, V, S);
+}
+
+fn f(_: HashMap$0<u32, u32, collections::RandomState>) {}
+"#,
+            r#"
+mod collections {
+    pub struct RandomState;
+    pub struct HashMap<K, V, S = RandomState>(K, V, S);
+}
+
+fn f(_: collections::HashMap<u32, u32, collections::RandomState>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_crate_visible_items() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod outer {
+    pub(crate) mod inner {
+        pub(crate) struct Visible;
+        pub(in crate::outer) struct Restricted;
+    }
+}
+
+mod user {
+    fn f() {
+        let _ = Visible$0;
+    }
+}
+"#,
+            expect![[r#"
+                Qualify Visible: Qualify as `crate::outer::inner::Visible`
+            "#]],
+        );
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod outer {
+    pub(crate) mod inner {
+        pub(in crate::outer) struct Restricted;
+    }
+}
+
+mod user {
+    fn f() {
+        let _ = Restricted$0;
+    }
+}
+"#,
+        );
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod outer {
+    pub(crate) mod inner {
+        pub(in crate::outer) struct Restricted;
+    }
+
+    fn f() {
+        let _ = Restricted$0;
+    }
+}
+"#,
+            expect![[r#"
+                Qualify Restricted: Qualify as `inner::Restricted`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn qualify_nested_generic_paths_one_after_another() {
+        check_assist(
+            qualify_path,
+            r#"
+mod collections {
+    pub struct Map<K, V>(K, V);
+    pub struct Vec<T>(T);
+}
+mod model {
+    pub struct Key;
+    pub struct Value;
+}
+use collections::Vec;
+use model::Key;
+
+fn f(_: Map$0<Key, Vec<Value>>) {}
+"#,
+            r#"
+mod collections {
+    pub struct Map<K, V>(K, V);
+    pub struct Vec<T>(T);
+}
+mod model {
+    pub struct Key;
+    pub struct Value;
+}
+use collections::Vec;
+use model::Key;
+
+fn f(_: collections::Map<Key, Vec<Value>>) {}
+"#,
+        );
+        check_assist(
+            qualify_path,
+            r#"
+mod collections {
+    pub struct Map<K, V>(K, V);
+    pub struct Vec<T>(T);
+}
+mod model {
+    pub struct Key;
+    pub struct Value;
+}
+use collections::Vec;
+use model::Key;
+
+fn f(_: collections::Map<Key, Vec<Val$0ue>>) {}
+"#,
+            r#"
+mod collections {
+    pub struct Map<K, V>(K, V);
+    pub struct Vec<T>(T);
+}
+mod model {
+    pub struct Key;
+    pub struct Value;
+}
+use collections::Vec;
+use model::Key;
+
+fn f(_: collections::Map<Key, Vec<model::Value>>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_attribute_macro() {
+        check_assist(
+            qualify_path,
+            r#"
+//- proc_macros: identity
+#[ident$0ity(get, "/")]
+fn route() {}
+"#,
+            r#"
+#[proc_macros::identity(get, "/")]
+fn route() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_in_module_file_of_multi_file_fixture() {
+        check_assist(
+            qualify_path,
+            r#"
+//- /lib.rs crate:lib
+pub mod items {
+    pub struct Item;
+}
+mod docs;
+//- /docs.rs
+mod example {
+    fn f() {
+        let _ = It$0em;
+    }
+}
+"#,
+            r#"
+mod example {
+    fn f() {
+        let _ = crate::items::Item;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_doc_comment() {
+        // code blocks of doc comments are only highlighted, not analyzed
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+pub mod items {
+    pub struct Item;
+}
+
+/// ```
+/// let _ = It$0em;
+/// ```
+pub fn f() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn qualify_inside_closure() {
+        check_assist(
+            qualify_path,
+            r#"
+mod util {
+    pub fn function(x: u32) -> u32 {
+        x
+    }
+}
+
+fn main() {
+    let y = 1;
+    let f = |x| func$0tion(x + y);
+}
+"#,
+            r#"
+mod util {
+    pub fn function(x: u32) -> u32 {
+        x
+    }
+}
+
+fn main() {
+    let y = 1;
+    let f = |x| util::function(x + y);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn links_the_unresolved_name_diagnostics() {
+        let (db, file_id, range_or_offset) = RootDatabase::with_range_or_offset(
+            r#"
+mod foo {
+    pub struct Bar;
+    pub trait Baz {
+        fn baz(&self) {}
+    }
+    impl Baz for Bar {}
+}
+
+fn main() {
+    let bar = Bar$0;
+    foo::Bar.baz();
+}
+"#,
+        );
+        let frange = FileRange { file_id, range: range_or_offset.into() };
+        let assist = crate::assists(&db, &TEST_CONFIG, AssistResolveStrategy::None, frange)
+            .into_iter()
+            .find(|assist| assist.id.0 == "qualify_path")
+            .unwrap();
+        assert!(assist.diagnostic_codes.contains(&"E0425"));
+        assert!(!assist.diagnostic_codes.contains(&"E0599"));
+
+        let (db, file_id, range_or_offset) = RootDatabase::with_range_or_offset(
+            r#"
+mod foo {
+    pub struct Bar;
+    pub trait Baz {
+        fn baz(&self) {}
+    }
+    impl Baz for Bar {}
+}
+
+fn main() {
+    foo::Bar.baz$0();
+}
+"#,
+        );
+        let frange = FileRange { file_id, range: range_or_offset.into() };
+        let assist = crate::assists(&db, &TEST_CONFIG, AssistResolveStrategy::None, frange)
+            .into_iter()
+            .find(|assist| assist.id.0 == "qualify_path")
+            .unwrap();
+        assert_eq!(assist.diagnostic_codes, ["E0599"]);
+    }
+
+    #[test]
+    fn functional_update_base() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Config {
+        pub verbose: bool,
+        pub level: u8,
+    }
+    impl Config {
+        pub fn new() -> Config {
+            Config { verbose: false, level: 0 }
+        }
+    }
+}
+
+fn main() {
+    let config = foo::Config { verbose: true, ..Config$0::new() };
+}
+"#,
+            r#"
+mod foo {
+    pub struct Config {
+        pub verbose: bool,
+        pub level: u8,
+    }
+    impl Config {
+        pub fn new() -> Config {
+            Config { verbose: false, level: 0 }
+        }
+    }
+}
+
+fn main() {
+    let config = foo::Config { verbose: true, ..foo::Config::new() };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn functional_update_base_value() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Config {
+        pub verbose: bool,
+        pub level: u8,
+    }
+    pub const DEFAULT: Config = Config { verbose: false, level: 0 };
+}
+
+fn main() {
+    let config = foo::Config { level: 3, ..DEFAULT$0 };
+}
+"#,
+            r#"
+mod foo {
+    pub struct Config {
+        pub verbose: bool,
+        pub level: u8,
+    }
+    pub const DEFAULT: Config = Config { verbose: false, level: 0 };
+}
+
+fn main() {
+    let config = foo::Config { level: 3, ..foo::DEFAULT };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn alias_does_not_resolve_original() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar as Baz;
+
+fn main() {
+    let _ = Baz;
+    let _ = Bar$0;
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar as Baz;
+
+fn main() {
+    let _ = Baz;
+    let _ = foo::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn alias_name_is_resolved() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar as Baz;
+
+fn main() {
+    let _ = Baz$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn where_clause_assoc_type_binding() {
+        check_assist(
+            qualify_path,
+            r#"
+//- minicore: iterator
+mod foo {
+    pub struct Foo;
+}
+
+fn collect<T>(iter: T)
+where
+    T: Iterator<Item = Foo$0>,
+{
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn collect<T>(iter: T)
+where
+    T: Iterator<Item = foo::Foo>,
+{
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn pattern_in_matches_macro() {
+        check_assist(
+            qualify_path,
+            r#"
+macro_rules! matches {
+    ($expression:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {
+        match $expression {
+            $pattern $(if $guard)? => true,
+            _ => false
+        }
+    };
+}
+mod foo {
+    pub enum Kind {
+        Unit,
+        Tuple(u8),
+    }
+}
+
+fn is_tuple(kind: foo::Kind) -> bool {
+    matches!(kind, Tuple$0(_))
+}
+"#,
+            r#"
+macro_rules! matches {
+    ($expression:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {
+        match $expression {
+            $pattern $(if $guard)? => true,
+            _ => false
+        }
+    };
+}
+mod foo {
+    pub enum Kind {
+        Unit,
+        Tuple(u8),
+    }
+}
+
+fn is_tuple(kind: foo::Kind) -> bool {
+    matches!(kind, foo::Kind::Tuple(_))
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn expr_in_vec_macro() {
+        check_assist(
+            qualify_path,
+            r#"
+macro_rules! vec {
+    ($($x:expr),* $(,)?) => { [$($x),*] };
+}
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn main() {
+    let foos = vec![Foo$0::new(), foo::Foo::new()];
+}
+"#,
+            r#"
+macro_rules! vec {
+    ($($x:expr),* $(,)?) => { [$($x),*] };
+}
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn main() {
+    let foos = vec![foo::Foo::new(), foo::Foo::new()];
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rustfmt_skip_keeps_the_formatting() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo<T>(T);
+}
+
+#[rustfmt::skip]
+fn main() {
+    let x   =   Foo$0 :: < u8 > ( 0 ) ;
+    let y   =   foo::Foo( 1 );
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo<T>(T);
+}
+
+#[rustfmt::skip]
+fn main() {
+    let x   =   foo::Foo :: < u8 > ( 0 ) ;
+    let y   =   foo::Foo( 1 );
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_of_generic_trait() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub trait Convert<T> {
+        fn convert<U>(&self, extra: U) -> T;
+    }
+    impl Convert<u32> for crate::Value {
+        fn convert<U>(&self, _: U) -> u32 {
+            0
+        }
+    }
+}
+struct Value;
+
+fn main() {
+    let value = Value;
+    let n: u32 = value.convert$0::<u8>(1);
+}
+"#,
+            r#"
+mod foo {
+    pub trait Convert<T> {
+        fn convert<U>(&self, extra: U) -> T;
+    }
+    impl Convert<u32> for crate::Value {
+        fn convert<U>(&self, _: U) -> u32 {
+            0
+        }
+    }
+}
+struct Value;
+
+fn main() {
+    let value = Value;
+    let n: u32 = foo::Convert::convert::<u8>(&value, 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_of_generic_trait_through_references() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub trait Convert<T> {
+        fn convert(&self) -> T;
+    }
+    impl Convert<u32> for crate::Value {
+        fn convert(&self) -> u32 {
+            0
+        }
+    }
+}
+struct Value;
+
+fn main() {
+    let value = &&Value;
+    let n: u32 = value.convert$0();
+}
+"#,
+            r#"
+mod foo {
+    pub trait Convert<T> {
+        fn convert(&self) -> T;
+    }
+    impl Convert<u32> for crate::Value {
+        fn convert(&self) -> u32 {
+            0
+        }
+    }
+}
+struct Value;
+
+fn main() {
+    let value = &&Value;
+    let n: u32 = foo::Convert::convert(&**value);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn array_element_type() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn first(items: [Foo$0; 4]) {}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn first(items: [foo::Foo; 4]) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn slice_element_type() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn len(items: &[Foo$0]) {}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn len(items: &[foo::Foo]) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn item_reachable_through_import_and_full_path_is_offered_once() {
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod std {
+    pub mod fmt {
+        pub struct Formatter;
+    }
+}
+
+use std::fmt;
+use std::*;
+
+fn f(_: Formatter$0) {}
+"#,
+            expect![[r#"
+                Qualify Formatter: Qualify as `fmt::Formatter`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unit_struct_statement_keeps_semicolon() {
+        check_assist(
+            qualify_path,
+            r#"
+pub mod PubMod {
+    pub struct PubStruct;
+}
+
+fn main() {
+    PubStruct$0;
+}
+"#,
+            r#"
+pub mod PubMod {
+    pub struct PubStruct;
+}
+
+fn main() {
+    PubMod::PubStruct;
+}
+"#,
+        );
+        check_assist_target(
+            qualify_path,
+            r#"
+pub mod PubMod {
+    pub struct PubStruct;
+}
+
+fn main() {
+    PubStruct$0;
+}
+"#,
+            "PubStruct",
+        );
+    }
+
+    #[test]
+    fn unresolved_qualifier_of_trait_function() {
+        // only the type is unresolved, the trait is in scope, so no UFCS is needed
+        cov_mark::check!(qualify_path_unqualified_name);
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_function();
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_function() {}
+    }
+}
+
+use test_mod::TestTrait;
+
+fn main() {
+    TestStruct$0::test_function();
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_function();
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_function() {}
+    }
+}
+
+use test_mod::TestTrait;
+
+fn main() {
+    test_mod::TestStruct::test_function();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn return_value() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    return Foo$0::new();
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    return foo::Foo::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn break_value() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    loop {
+        break Foo$0::new();
+    }
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    loop {
+        break foo::Foo::new();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_return_value() {
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    let make = || return Foo$0::new();
+    make()
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+    impl Foo {
+        pub fn new() -> Foo {
+            Foo
+        }
+    }
+}
+
+fn make() -> foo::Foo {
+    let make = || return foo::Foo::new();
+    make()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn self_prefix_with_by_self_import_style() {
+        let config = AssistConfig {
+            insert_use: InsertUseConfig {
+                prefix_kind: hir::PrefixKind::BySelf,
+                ..TEST_CONFIG.insert_use
+            },
+            ..TEST_CONFIG
+        };
+        check_assist_with_config(
+            qualify_path,
+            config.clone(),
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+fn main() {
+    Bar$0;
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+fn main() {
+    self::foo::Bar;
+}
+"#,
+        );
+        check_assist_with_config(
+            qualify_path,
+            config,
+            r#"
+mod sub {
+    mod foo {
+        pub struct Bar;
+    }
+
+    fn main() {
+        Bar$0;
+    }
+}
+"#,
+            r#"
+mod sub {
+    mod foo {
+        pub struct Bar;
+    }
+
+    fn main() {
+        self::foo::Bar;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_asm_sym_operand() {
+        // `asm!` expands to its template strings only, so its operands are never resolved
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+#[rustc_builtin_macro]
+macro_rules! asm {}
+mod foo {
+    pub extern "C" fn my_func() {}
+}
+
+fn main() {
+    unsafe {
+        asm!("call {}", sym my_f$0unc);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn partial_paths_while_typing() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Foo::<u8, $0
+}
+"#,
+        );
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = foo::Foo.$0
+}
+"#,
+        );
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Foo::$0
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = foo::Foo::
+}
+"#,
+        );
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Foo::<$0
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = foo::Foo::<
+}
+"#,
+        );
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = Foo$0::bar::
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = foo::Foo::bar::
+}
+"#,
+        );
+        check_assist(
+            qualify_path,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = <Foo$0 as>::x
+}
+"#,
+            r#"
+mod foo {
+    pub struct Foo;
+}
+
+fn main() {
+    let _ = <foo::Foo as>::x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn range_expr_bound() {
+        check_assist(
+            qualify_path,
+            r#"
+mod limits {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 9;
+}
+
+fn main() {
+    let range = MI$0N..=limits::MAX;
+}
+"#,
+            r#"
+mod limits {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 9;
+}
+
+fn main() {
+    let range = limits::MIN..=limits::MAX;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn range_pattern_bound() {
+        check_assist(
+            qualify_path,
+            r#"
+mod limits {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 9;
+}
+
+fn in_range(n: u8) -> bool {
+    match n {
+        MI$0N..=limits::MAX => true,
+        _ => false,
+    }
+}
+"#,
+            r#"
+mod limits {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 9;
+}
+
+fn in_range(n: u8) -> bool {
+    match n {
+        limits::MIN..=limits::MAX => true,
+        _ => false,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_binding_pattern() {
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod limits {
+    pub const MIN: u8 = 1;
+}
+
+fn main() {
+    let MI$0N = 0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn struct_in_type_position_over_trait() {
+        cov_mark::check!(qualify_path_fitting_namespace);
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod shapes {
+    pub struct Foo;
+}
+mod traits {
+    pub trait Foo {}
+}
+
+fn f(_: Foo$0) {}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `shapes::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trait_in_bound_over_struct() {
+        cov_mark::check!(qualify_path_fitting_namespace);
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod shapes {
+    pub struct Foo;
+}
+mod traits {
+    pub trait Foo {}
+}
+
+fn f<T: Foo$0>(_: T) {}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `traits::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trait_of_impl_over_struct() {
+        cov_mark::check!(qualify_path_fitting_namespace);
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod shapes {
+    pub struct Foo;
+}
+mod traits {
+    pub trait Foo {}
+}
+
+struct S;
+impl Foo$0 for S {}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `traits::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn struct_in_expression_over_trait() {
+        cov_mark::check!(qualify_path_fitting_namespace);
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod shapes {
+    pub struct Foo;
+}
+mod traits {
+    pub trait Foo {}
+}
+
+fn main() {
+    let _ = Foo$0;
+}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `shapes::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn self_type_of_impl_over_trait() {
+        cov_mark::check!(qualify_path_fitting_namespace);
+        check_assist_labels(
+            qualify_path,
+            r#"
+mod shapes {
+    pub struct Foo;
+}
+mod traits {
+    pub trait Foo {}
+}
+
+impl Foo$0 {}
+"#,
+            expect![[r#"
+                Qualify Foo: Qualify as `shapes::Foo`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn default_of_type_param() {
+        check_assist(
+            qualify_path,
+            r#"
+mod defaults {
+    pub struct Unit;
+}
+
+struct S<T = Un$0it>(T);
+"#,
+            r#"
+mod defaults {
+    pub struct Unit;
+}
+
+struct S<T = defaults::Unit>(T);
+"#,
+        );
+    }
+
+    #[test]
+    fn default_of_const_param() {
+        check_assist(
+            qualify_path,
+            r#"
+mod defaults {
+    pub const LEN: usize = 4;
+}
+
+struct S<const N: usize = { LEN$0 }>([u8; N]);
+"#,
+            r#"
+mod defaults {
+    pub const LEN: usize = 4;
+}
+
+struct S<const N: usize = { defaults::LEN }>([u8; N]);
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_with_macro_receiver() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! make {
+    () => { test_mod::TestStruct {} };
+}
+
+fn main() {
+    make!().test_meth$0od()
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! make {
+    () => { test_mod::TestStruct {} };
+}
+
+fn main() {
+    test_mod::TestTrait::test_method(&make!())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_method_call_in_macro_input() {
+        check_assist(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! id {
+    ($e:expr) => { $e };
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    id!(test_struct.test_meth$0od())
+}
+"#,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! id {
+    ($e:expr) => { $e };
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    id!(test_mod::TestTrait::test_method(&test_struct))
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_trait_method_with_receiver_from_macro() {
+        cov_mark::check!(qualify_path_trait_method_assembled_by_macro);
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! call {
+    ($m:ident) => { test_mod::TestStruct {}.$m() };
+}
+
+fn main() {
+    call!(test_meth$0od)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_trait_method_call_assembled_by_macro() {
+        cov_mark::check!(qualify_path_trait_method_assembled_by_macro);
+        check_assist_not_applicable(
+            qualify_path,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+    }
+}
+macro_rules! call {
+    ($r:expr, $m:ident) => { $r.$m() };
+}
+
+fn main() {
+    let test_struct = test_mod::TestStruct {};
+    call!(test_struct, test_meth$0od)
+}
+"#,
+        );
+    }
 }
@@ -1,7 +1,8 @@
-use hir::AsAssocItem;
+use hir::{AsAssocItem, ModPath};
 use ide_db::{
     helpers::mod_path_to_ast,
-    imports::insert_use::{insert_use, ImportScope},
+    imports::insert_use::{insert_use, insert_use_as_alias, ImportScope},
+    source_change::SourceChangeBuilder,
 };
 use syntax::{
     ast::{self, make},
@@ -10,6 +11,9 @@ use syntax::{
 
 use crate::{AssistContext, AssistId, AssistKind, Assists};
 
+/// Paths with at least this many segments are considered long enough to be worth importing.
+const LONG_PATH_SEGMENTS: usize = 4;
+
 // Assist: replace_qualified_name_with_use
 //
 // Adds a use statement for a given fully-qualified name.
@@ -29,6 +33,110 @@ pub(crate) fn replace_qualified_name_with_use(
     acc: &mut Assists,
     ctx: &AssistContext<'_>,
 ) -> Option<()> {
+    let (path, path_to_qualifier) = importable_qualified_path(ctx)?;
+
+    let scope = ImportScope::find_insert_use_container(path.syntax(), &ctx.sema)?;
+    let target = path.syntax().text_range();
+    // for long paths, the point of the import is making them readable
+    let name = path.segment().and_then(|it| it.name_ref());
+    let label = match name {
+        Some(name) if path.segments().count() >= LONG_PATH_SEGMENTS => {
+            format!("Simplify `{name}` with use")
+        }
+        _ => "Replace qualified path with use".to_owned(),
+    };
+    acc.add(
+        AssistId("replace_qualified_name_with_use", AssistKind::RefactorRewrite),
+        label,
+        target,
+        |builder| replace_with_use(ctx, builder, scope, &path, path_to_qualifier, None),
+    )
+}
+
+// Assist: replace_qualified_name_with_aliased_use
+//
+// Adds a renamed use statement for a given fully-qualified name, if its name is already taken in
+// the current scope.
+//
+// ```
+// # mod std { pub mod fmt { pub struct Formatter; } }
+// struct Formatter;
+// fn fmt(f: std::fmt::$0Formatter) {}
+// ```
+// ->
+// ```
+// use std::fmt::Formatter as Formatter2;
+//
+// # mod std { pub mod fmt { pub struct Formatter; } }
+// struct Formatter;
+// fn fmt(f: Formatter2) {}
+// ```
+pub(crate) fn replace_qualified_name_with_aliased_use(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let (path, path_to_qualifier) = importable_qualified_path(ctx)?;
+
+    let name = path.segment()?.name_ref()?;
+    let sema_scope = ctx.sema.scope(path.syntax())?;
+    let resolution = ctx.sema.resolve_path(&path)?;
+    match sema_scope.speculative_resolve(&make::ext::ident_path(&name.text())) {
+        Some(existing) if existing != resolution => (),
+        _ => {
+            cov_mark::hit!(aliased_use_no_collision);
+            return None;
+        }
+    }
+    let alias = (2..)
+        .map(|idx| format!("{name}{idx}"))
+        .find(|alias| sema_scope.speculative_resolve(&make::ext::ident_path(alias)).is_none())?;
+
+    let scope = ImportScope::find_insert_use_container(path.syntax(), &ctx.sema)?;
+    let target = path.syntax().text_range();
+    acc.add(
+        AssistId("replace_qualified_name_with_aliased_use", AssistKind::RefactorRewrite),
+        format!("Replace qualified path with use as `{alias}`"),
+        target,
+        |builder| replace_with_use(ctx, builder, scope, &path, path_to_qualifier, Some(&alias)),
+    )
+}
+
+/// Imports `path` into `scope`, renamed to `alias` if given, and refers to it by its name in the
+/// whole scope.
+fn replace_with_use(
+    ctx: &AssistContext<'_>,
+    builder: &mut SourceChangeBuilder,
+    scope: ImportScope,
+    path: &ast::Path,
+    path_to_qualifier: Option<ModPath>,
+    alias: Option<&str>,
+) {
+    // Now that we've brought the name into scope, re-qualify all paths that could be
+    // affected (that is, all paths inside the node we added the `use` to).
+    let scope = match scope {
+        ImportScope::File(it) => ImportScope::File(builder.make_mut(it)),
+        ImportScope::Module(it) => ImportScope::Module(builder.make_mut(it)),
+        ImportScope::Block(it) => ImportScope::Block(builder.make_mut(it)),
+    };
+    shorten_paths(scope.as_syntax_node(), path, alias);
+    let path = drop_generic_args(path);
+    // stick the found import in front of the to be replaced path
+    let path = match path_to_qualifier.and_then(|it| mod_path_to_ast(&it).qualifier()) {
+        Some(qualifier) => make::path_concat(qualifier, path),
+        None => path,
+    };
+    match alias {
+        Some(alias) => {
+            let alias = make::rename(make::name(alias));
+            insert_use_as_alias(&scope, path, &ctx.config.insert_use, alias);
+        }
+        None => insert_use(&scope, path, &ctx.config.insert_use),
+    }
+}
+
+/// Finds the qualified path under the cursor which can be imported, along with the path to its
+/// qualifier from the current module.
+fn importable_qualified_path(ctx: &AssistContext<'_>) -> Option<(ast::Path, Option<ModPath>)> {
     let path: ast::Path = ctx.find_node_at_offset()?;
     // We don't want to mess with use statements
     if path.syntax().ancestors().find_map(ast::UseTree::cast).is_some() {
@@ -72,30 +180,7 @@ pub(crate) fn replace_qualified_name_with_use(
         })
         .flatten();
 
-    let scope = ImportScope::find_insert_use_container(path.syntax(), &ctx.sema)?;
-    let target = path.syntax().text_range();
-    acc.add(
-        AssistId("replace_qualified_name_with_use", AssistKind::RefactorRewrite),
-        "Replace qualified path with use",
-        target,
-        |builder| {
-            // Now that we've brought the name into scope, re-qualify all paths that could be
-            // affected (that is, all paths inside the node we added the `use` to).
-            let scope = match scope {
-                ImportScope::File(it) => ImportScope::File(builder.make_mut(it)),
-                ImportScope::Module(it) => ImportScope::Module(builder.make_mut(it)),
-                ImportScope::Block(it) => ImportScope::Block(builder.make_mut(it)),
-            };
-            shorten_paths(scope.as_syntax_node(), &path);
-            let path = drop_generic_args(&path);
-            // stick the found import in front of the to be replaced path
-            let path = match path_to_qualifier.and_then(|it| mod_path_to_ast(&it).qualifier()) {
-                Some(qualifier) => make::path_concat(qualifier, path),
-                None => path,
-            };
-            insert_use(&scope, path, &ctx.config.insert_use);
-        },
-    )
+    Some((path, path_to_qualifier))
 }
 
 fn drop_generic_args(path: &ast::Path) -> ast::Path {
@@ -108,8 +193,8 @@ fn drop_generic_args(path: &ast::Path) -> ast::Path {
     path
 }
 
-/// Mutates `node` to shorten `path` in all descendants of `node`.
-fn shorten_paths(node: &SyntaxNode, path: &ast::Path) {
+/// Mutates `node` to shorten `path` in all descendants of `node`, renaming it to `alias` if given.
+fn shorten_paths(node: &SyntaxNode, path: &ast::Path, alias: Option<&str>) {
     for child in node.children() {
         match_ast! {
             match child {
@@ -119,20 +204,25 @@ fn shorten_paths(node: &SyntaxNode, path: &ast::Path) {
                 // Don't descend into submodules, they don't have the same `use` items in scope.
                 // FIXME: This isn't true due to `super::*` imports?
                 ast::Module(_) => continue,
-                ast::Path(p) => if maybe_replace_path(p.clone(), path.clone()).is_none() {
-                    shorten_paths(p.syntax(), path);
+                ast::Path(p) => if maybe_replace_path(p.clone(), path.clone(), alias).is_none() {
+                    shorten_paths(p.syntax(), path, alias);
                 },
-                _ => shorten_paths(&child, path),
+                _ => shorten_paths(&child, path, alias),
             }
         }
     }
 }
 
-fn maybe_replace_path(path: ast::Path, target: ast::Path) -> Option<()> {
+fn maybe_replace_path(path: ast::Path, target: ast::Path, alias: Option<&str>) -> Option<()> {
     if !path_eq_no_generics(path.clone(), target) {
         return None;
     }
 
+    if let Some(alias) = alias {
+        let name_ref = path.segment()?.name_ref()?;
+        ted::replace(name_ref.syntax(), make::name_ref(alias).clone_for_update().syntax());
+    }
+
     // Shorten `path`, leaving only its last segment.
     if let Some(parent) = path.qualifier() {
         ted::remove(parent.syntax());
@@ -171,7 +261,7 @@ fn path_eq_no_generics(lhs: ast::Path, rhs: ast::Path) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable};
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
 
     use super::*;
 
@@ -436,4 +526,139 @@ fn main() {
 ",
         );
     }
+
+    #[test]
+    fn aliased_use_on_collision() {
+        check_assist(
+            replace_qualified_name_with_aliased_use,
+            r"
+mod foo { pub struct Foo; }
+struct Foo;
+
+fn main() {
+    let _: Foo = Foo;
+    let _: foo::Foo$0 = foo::Foo;
+}
+",
+            r"
+use foo::Foo as Foo2;
+
+mod foo { pub struct Foo; }
+struct Foo;
+
+fn main() {
+    let _: Foo = Foo;
+    let _: Foo2 = Foo2;
+}
+",
+        );
+    }
+
+    #[test]
+    fn aliased_use_skips_taken_aliases() {
+        check_assist(
+            replace_qualified_name_with_aliased_use,
+            r"
+mod foo { pub struct Foo<T>(T); }
+struct Foo;
+struct Foo2;
+
+fn main() {
+    let _: foo::Foo$0<()>;
+}
+",
+            r"
+use foo::Foo as Foo3;
+
+mod foo { pub struct Foo<T>(T); }
+struct Foo;
+struct Foo2;
+
+fn main() {
+    let _: Foo3<()>;
+}
+",
+        );
+    }
+
+    #[test]
+    fn aliased_use_not_applicable_without_collision() {
+        cov_mark::check!(aliased_use_no_collision);
+        check_assist_not_applicable(
+            replace_qualified_name_with_aliased_use,
+            r"
+mod foo { pub struct Foo; }
+
+fn main() {
+    foo::Foo$0;
+}
+",
+        );
+    }
+
+    #[test]
+    fn simplify_long_path() {
+        check_assist_by_label(
+            replace_qualified_name_with_use,
+            r"
+mod a { pub mod b { pub mod c { pub struct D; } } }
+fn main() {
+    let _ = a::b::c::D$0;
+}
+",
+            r"
+use a::b::c::D;
+
+mod a { pub mod b { pub mod c { pub struct D; } } }
+fn main() {
+    let _ = D;
+}
+",
+            "Simplify `D` with use",
+        );
+    }
+
+    #[test]
+    fn simplify_label_leaves_out_generic_args() {
+        check_assist_by_label(
+            replace_qualified_name_with_use,
+            r"
+mod a { pub mod b { pub mod c { pub struct D<T>(T); } } }
+fn main() {
+    let _: a::b::c::D$0<(u8, u16, u32, u64)>;
+}
+",
+            r"
+use a::b::c::D;
+
+mod a { pub mod b { pub mod c { pub struct D<T>(T); } } }
+fn main() {
+    let _: D<(u8, u16, u32, u64)>;
+}
+",
+            "Simplify `D` with use",
+        );
+    }
+
+    #[test]
+    fn short_path_is_not_simplified() {
+        check_assist_by_label(
+            replace_qualified_name_with_use,
+            r"
+mod a { pub mod b { pub struct C; } }
+fn main() {
+    let _ = a::b::C$0;
+}
+",
+            r"
+use a::b::C;
+
+mod a { pub mod b { pub struct C; } }
+fn main() {
+    let _ = C;
+}
+",
+            "Replace qualified path with use",
+        );
+    }
 }
@@ -0,0 +1,417 @@
+use hir::PathResolution;
+use ide_db::{defs::Definition, helpers::mod_path_to_ast, search::SearchScope};
+use syntax::{
+    ast::{self, edit_in_place::Removable, make, HasModuleItem, HasVisibility},
+    match_ast, ted, AstNode,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    AssistId, AssistKind,
+};
+
+// Assist: replace_use_with_qualified_path
+//
+// Removes a `use` item and qualifies the only usage of the imported name instead.
+//
+// ```
+// mod std { pub mod collections { pub struct HashMap<K, V>(K, V); } }
+// use std::collections::HashMap$0;
+//
+// fn process(map: HashMap<String, String>) {}
+// ```
+// ->
+// ```
+// mod std { pub mod collections { pub struct HashMap<K, V>(K, V); } }
+//
+// fn process(map: std::collections::HashMap<String, String>) {}
+// ```
+pub(crate) fn replace_use_with_qualified_path(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let use_tree: ast::UseTree = ctx.find_node_at_offset()?;
+    let use_item = ast::Use::cast(use_tree.syntax().parent()?)?;
+    // re-exports can be used from anywhere, even from other crates
+    if use_item.visibility().is_some() {
+        cov_mark::hit!(replace_use_reexport);
+        return None;
+    }
+    if use_tree.star_token().is_some()
+        || use_tree.use_tree_list().is_some()
+        || use_tree.rename().is_some()
+    {
+        return None;
+    }
+    let path = use_tree.path()?;
+    let qualifier = match ctx.sema.resolve_path(&path.qualifier()?)? {
+        PathResolution::Def(def) => def,
+        _ => return None,
+    };
+    let def = match ctx.sema.resolve_path(&path)? {
+        PathResolution::Def(def) => Definition::from(def),
+        _ => return None,
+    };
+    let module = ctx.sema.scope(use_item.syntax())?.module();
+    // spell the qualifier like other paths in the module do, rather than like the `use` does
+    let qualifier =
+        mod_path_to_ast(&module.find_use_path(ctx.db(), qualifier, ctx.config.prefer_no_std)?);
+
+    // Only usages which are resolved through this import are of interest, that is unqualified
+    // ones outside of other imports and in the same module. Child modules can still reach the
+    // import through their parent though, and would lose it. A private import can't be used
+    // from anywhere else.
+    let scope = SearchScope::module_and_children(ctx.db(), module);
+    let mut usages = Vec::new();
+    for usage in def.usages(&ctx.sema).in_scope(scope).all().references.into_values().flatten() {
+        let Some(usage) = usage.name.as_name_ref().and_then(usage_path) else { continue };
+        let usage_module = ctx.sema.scope(usage.syntax()).map(|it| it.module());
+        if usage.qualifier().is_none()
+            && !usage.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind()))
+            && usage_module == Some(module)
+        {
+            usages.push(usage);
+        } else if reaches_through(ctx, &usage, module) {
+            cov_mark::hit!(replace_use_used_by_child_module);
+            return None;
+        }
+    }
+    let mut usages = usages.into_iter();
+    let usage = usages.next()?;
+    if usages.next().is_some() {
+        cov_mark::hit!(replace_use_multiple_usages);
+        return None;
+    }
+    // usages inside of macro calls are found in the expansion, which can't be edited
+    if usage.syntax().ancestors().last() != use_item.syntax().ancestors().last() {
+        return None;
+    }
+    // keep the segment of the usage, it might carry generic arguments
+    let segment = usage.segment()?;
+
+    acc.add(
+        AssistId("replace_use_with_qualified_path", AssistKind::RefactorInline),
+        "Replace `use` with qualified path",
+        use_item.syntax().text_range(),
+        |builder| {
+            let qualified = make::path_concat(qualifier, make::path_unqualified(segment));
+            let usage = builder.make_mut(usage);
+            let use_item = builder.make_mut(use_item);
+            ted::replace(usage.syntax(), qualified.clone_for_update().syntax());
+            use_item.remove();
+        },
+    )
+}
+
+fn usage_path(name_ref: &ast::NameRef) -> Option<ast::Path> {
+    name_ref.syntax().parent().and_then(ast::PathSegment::cast)?.parent_path().into()
+}
+
+/// Whether `usage` might name the item through the names of `module`, like `super::Bar` or `Bar`
+/// after a `use super::*;`.
+fn reaches_through(ctx: &AssistContext<'_>, usage: &ast::Path, module: hir::Module) -> bool {
+    let names_module = |path: &ast::Path| {
+        matches!(
+            ctx.sema.resolve_path(path),
+            Some(PathResolution::Def(hir::ModuleDef::Module(it))) if it == module
+        )
+    };
+    if let Some(qualifier) = usage.qualifier() {
+        return names_module(&qualifier);
+    }
+    // the items of the module the usage is in, be it inline or a file of its own
+    let Some(items) = usage.syntax().ancestors().find_map(|it| {
+        match_ast! {
+            match it {
+                ast::ItemList(it) => Some(it.items().collect::<Vec<_>>()),
+                ast::SourceFile(it) => Some(it.items().collect()),
+                _ => None,
+            }
+        }
+    }) else {
+        return false;
+    };
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            ast::Item::Use(it) => it.use_tree(),
+            _ => None,
+        })
+        .flat_map(|tree| tree.syntax().descendants().filter_map(ast::UseTree::cast))
+        .filter(|tree| tree.star_token().is_some())
+        .any(|tree| tree.path().map_or(false, |path| names_module(&path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replace_single_usage() {
+        check_assist(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+fn main() {
+    let _ = foo::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_module_usage() {
+        check_assist(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub mod bar {
+        pub fn baz() {}
+    }
+}
+use foo::bar$0;
+
+fn main() {
+    bar::baz();
+}
+"#,
+            r#"
+mod foo {
+    pub mod bar {
+        pub fn baz() {}
+    }
+}
+
+fn main() {
+    foo::bar::baz();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn ignores_usages_in_other_modules() {
+        check_assist(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+
+mod other {
+    use crate::foo::Bar;
+
+    fn f() {
+        let _ = Bar;
+    }
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+fn main() {
+    let _ = foo::Bar;
+}
+
+mod other {
+    use crate::foo::Bar;
+
+    fn f() {
+        let _ = Bar;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_with_path_relative_to_module() {
+        check_assist(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use self::foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+"#,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+
+fn main() {
+    let _ = foo::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_reexport() {
+        cov_mark::check!(replace_use_reexport);
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+//- /main.rs
+mod foo {
+    pub struct Bar;
+}
+mod other;
+pub(crate) use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+//- /other.rs
+fn f() {
+    let _ = crate::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_usage_through_super() {
+        cov_mark::check!(replace_use_used_by_child_module);
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+
+mod tests {
+    fn f() {
+        let _ = super::Bar;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_usage_through_glob_of_parent() {
+        cov_mark::check!(replace_use_used_by_child_module);
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+}
+
+mod tests {
+    use super::*;
+
+    fn f() {
+        let _ = Bar;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_multiple_usages() {
+        cov_mark::check!(replace_use_multiple_usages);
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    let _ = Bar;
+    let _ = Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_glob_import() {
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::*$0;
+
+fn main() {
+    let _ = Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_usages() {
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_usage_in_macro_call() {
+        check_assist_not_applicable(
+            replace_use_with_qualified_path,
+            r#"
+macro_rules! id {
+    ($($tt:tt)*) => { $($tt)* };
+}
+mod foo {
+    pub struct Bar;
+}
+use foo::Bar$0;
+
+fn main() {
+    id!(let _ = Bar;);
+}
+"#,
+        );
+    }
+}
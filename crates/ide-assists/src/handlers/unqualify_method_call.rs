@@ -1,3 +1,8 @@
+use hir::AsAssocItem;
+use ide_db::{
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+};
 use syntax::{
     ast::{self, make, AstNode, HasArgList},
     TextRange,
@@ -7,7 +12,9 @@ use crate::{AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: unqualify_method_call
 //
-// Transforms universal function call syntax into a method call.
+// Transforms universal function call syntax into a method call. A borrow of a place matching
+// the `self` parameter is left to autoref. If the trait of the method is not in scope, it is
+// imported.
 //
 // ```
 // fn main() {
@@ -17,6 +24,8 @@ use crate::{AssistContext, AssistId, AssistKind, Assists};
 // ```
 // ->
 // ```
+// use std::ops::Add;
+//
 // fn main() {
 //     1.add(2);
 // }
@@ -33,7 +42,6 @@ pub(crate) fn unqualify_method_call(acc: &mut Assists, ctx: &AssistContext<'_>)
     }
 
     let args = call.arg_list()?;
-    let l_paren = args.l_paren_token()?;
     let mut args_iter = args.args();
     let first_arg = args_iter.next()?;
     let second_arg = args_iter.next();
@@ -43,17 +51,75 @@ pub(crate) fn unqualify_method_call(acc: &mut Assists, ctx: &AssistContext<'_>)
 
     let res = ctx.sema.resolve_path(&path)?;
     let hir::PathResolution::Def(hir::ModuleDef::Function(fun)) = res else { return None };
-    if !fun.has_self_param(ctx.sema.db) {
+    let self_param = fun.self_param(ctx.db())?;
+
+    // `&x` and `&mut x` are taken by autoref when the method borrows `self` the same way
+    let receiver = match &first_arg {
+        ast::Expr::RefExpr(ref_expr) if ref_expr.raw_token().is_none() => {
+            let access = match ref_expr.mut_token() {
+                Some(_) => hir::Access::Exclusive,
+                None => hir::Access::Shared,
+            };
+            let inner = ref_expr.expr()?;
+            let inner_is_ref = ctx.sema.type_of_expr(&inner)?.original.is_reference();
+            if self_param.access(ctx.db()) == access && !inner_is_ref && is_place(ctx, &inner) {
+                inner
+            } else {
+                first_arg.clone()
+            }
+        }
+        _ => first_arg.clone(),
+    };
+
+    // The method call has to resolve to the same method, with the trait imported if needed.
+    let scope = ctx.sema.scope(call.syntax())?;
+    let mut traits_in_scope = scope.visible_traits().0;
+    let trait_ = fun.as_assoc_item(ctx.db())?.containing_trait_or_trait_impl(ctx.db());
+    let import = match trait_ {
+        Some(trait_) if !traits_in_scope.contains(&trait_.into()) => {
+            traits_in_scope.insert(trait_.into());
+            let path = scope.module().find_use_path(
+                ctx.db(),
+                hir::ModuleDef::Trait(trait_),
+                ctx.config.prefer_no_std,
+            );
+            let import_scope = ImportScope::find_insert_use_container(call.syntax(), &ctx.sema);
+            path.zip(import_scope)
+        }
+        _ => None,
+    };
+    let receiver_ty = ctx.sema.type_of_expr(&receiver)?.original;
+    let resolved = receiver_ty.iterate_method_candidates_with_traits(
+        ctx.db(),
+        &scope,
+        &traits_in_scope,
+        None,
+        Some(&fun.name(ctx.db())),
+        Some,
+    );
+    // an implementation of the trait method is the same method as far as the call is concerned
+    let is_same = |candidate: hir::Function| {
+        candidate == fun
+            || trait_.is_some()
+                && candidate
+                    .as_assoc_item(ctx.db())
+                    .and_then(|it| it.containing_trait_or_trait_impl(ctx.db()))
+                    == trait_
+    };
+    if resolved.map_or(false, |it| !is_same(it)) {
+        cov_mark::hit!(unqualify_method_call_shadowed);
         return None;
     }
 
-    // `core::ops::Add::add(` -> ``
+    let needs_parens = needs_parens_as_receiver(&receiver);
+
+    // `core::ops::Add::add(&` -> ``
     let delete_path =
-        TextRange::new(path.syntax().text_range().start(), l_paren.text_range().end());
+        TextRange::new(path.syntax().text_range().start(), receiver.syntax().text_range().start());
 
     // Parens around `expr` if needed
-    let parens = needs_parens_as_receiver(&first_arg).then(|| {
-        let range = first_arg.syntax().text_range();
+    let parens = needs_parens.then(|| {
+        let range = receiver.syntax().text_range();
         (range.start(), range.end())
     });
 
@@ -65,9 +131,14 @@ pub(crate) fn unqualify_method_call(acc: &mut Assists, ctx: &AssistContext<'_>)
             .unwrap_or_else(|| first_arg.syntax().text_range().end()),
     );
 
+    // the method call doesn't compile without the trait in scope
+    let label = match &import {
+        Some((path, _)) => format!("Unqualify method call and import `{path}`"),
+        None => "Unqualify method call".to_owned(),
+    };
     acc.add(
         AssistId("unqualify_method_call", AssistKind::RefactorRewrite),
-        "Unqualify method call",
+        label,
         call.syntax().text_range(),
         |edit| {
             edit.delete(delete_path);
@@ -76,10 +147,35 @@ pub(crate) fn unqualify_method_call(acc: &mut Assists, ctx: &AssistContext<'_>)
                 edit.insert(close, ")");
             }
             edit.replace(replace_comma, format!(".{method_name}("));
+
+            if let Some((path, import_scope)) = import {
+                let import_scope = match import_scope {
+                    ImportScope::File(it) => ImportScope::File(edit.make_mut(it)),
+                    ImportScope::Module(it) => ImportScope::Module(edit.make_mut(it)),
+                    ImportScope::Block(it) => ImportScope::Block(edit.make_mut(it)),
+                };
+                insert_use(&import_scope, mod_path_to_ast(&path), &ctx.config.insert_use);
+            }
         },
     )
 }
 
+/// Whether borrowing `expr` borrows an existing place rather than a temporary.
+fn is_place(ctx: &AssistContext<'_>, expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::PathExpr(it) => matches!(
+            it.path().and_then(|path| ctx.sema.resolve_path(&path)),
+            Some(
+                hir::PathResolution::Local(_) | hir::PathResolution::Def(hir::ModuleDef::Static(_))
+            )
+        ),
+        ast::Expr::FieldExpr(_) | ast::Expr::IndexExpr(_) => true,
+        ast::Expr::PrefixExpr(it) => it.op_kind() == Some(ast::UnaryOp::Deref),
+        ast::Expr::ParenExpr(it) => it.expr().map_or(false, |it| is_place(ctx, &it)),
+        _ => false,
+    }
+}
+
 fn needs_parens_as_receiver(expr: &ast::Expr) -> bool {
     // Make `(expr).dummy()`
     let dummy_call = make::expr_method_call(
@@ -100,7 +196,7 @@ fn needs_parens_as_receiver(expr: &ast::Expr) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable};
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
 
     use super::*;
 
@@ -127,6 +223,8 @@ fn f() { S.f(S); }"#,
 //- minicore: add
 fn f() { <u32 as core::ops::Add>::$0add(2, 2); }"#,
             r#"
+use core::ops::Add;
+
 fn f() { 2.add(2); }"#,
         );
 
@@ -136,6 +234,8 @@ fn f() { 2.add(2); }"#,
 //- minicore: add
 fn f() { core::ops::Add::$0add(2, 2); }"#,
             r#"
+use core::ops::Add;
+
 fn f() { 2.add(2); }"#,
         );
 
@@ -179,6 +279,8 @@ impl core::ops::Deref for S {
 }
 fn f() { core::ops::Deref::$0deref(&S); }"#,
             r#"
+use core::ops::Deref;
+
 struct S;
 impl core::ops::Deref for S {
     type Target = S;
@@ -208,4 +310,279 @@ impl S { fn assoc(S: S, S: S) {} }
 fn f() { S::assoc$0(S, S); }"#,
         );
     }
+
+    #[test]
+    fn unqualify_trait_method() {
+        check_assist_by_label(
+            unqualify_method_call,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_meth$0od(&test_struct)
+}
+"#,
+            r#"
+use test_mod::TestTrait;
+
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_struct.test_method()
+}
+"#,
+            "Unqualify method call and import `test_mod::TestTrait`",
+        );
+    }
+
+    #[test]
+    fn unqualify_trait_method_mut() {
+        check_assist_by_label(
+            unqualify_method_call,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_meth$0od_mut(&mut test_struct)
+}
+"#,
+            r#"
+use test_mod::TestTrait;
+
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_struct.test_method_mut()
+}
+"#,
+            "Unqualify method call and import `test_mod::TestTrait`",
+        );
+    }
+
+    #[test]
+    fn unqualify_trait_method_multi_params() {
+        check_assist_by_label(
+            unqualify_method_call,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_meth$0od_params(&test_struct, 42)
+}
+"#,
+            r#"
+use test_mod::TestTrait;
+
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_struct.test_method_params(42)
+}
+"#,
+            "Unqualify method call and import `test_mod::TestTrait`",
+        );
+    }
+
+    #[test]
+    fn unqualify_trait_method_consume() {
+        check_assist_by_label(
+            unqualify_method_call,
+            r#"
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_mod::TestTrait::test_meth$0od_consume(test_struct)
+}
+"#,
+            r#"
+use test_mod::TestTrait;
+
+mod test_mod {
+    pub trait TestTrait {
+        fn test_method(&self);
+        fn test_method_mut(&mut self);
+        fn test_method_consume(self);
+        fn test_method_params(&self, test: i32);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test_method(&self) {}
+        fn test_method_mut(&mut self) {}
+        fn test_method_consume(self) {}
+        fn test_method_params(&self, test: i32) {}
+    }
+}
+
+fn main() {
+    let mut test_struct = test_mod::TestStruct {};
+    test_struct.test_method_consume()
+}
+"#,
+            "Unqualify method call and import `test_mod::TestTrait`",
+        );
+    }
+
+    #[test]
+    fn unqualify_trait_method_keeps_arguments_as_written() {
+        check_assist(
+            unqualify_method_call,
+            r#"
+//- minicore: add
+fn f() { core::ops::Add::$0add(1, 2 /* the other summand */); }"#,
+            r#"
+use core::ops::Add;
+
+fn f() { 1.add(2 /* the other summand */); }"#,
+        );
+    }
+
+    #[test]
+    fn unqualify_method_call_keeps_mismatched_borrow() {
+        check_assist(
+            unqualify_method_call,
+            r#"
+struct S;
+impl S { fn f(&self) {} }
+fn f() { S::$0f(&&S); }"#,
+            r#"
+struct S;
+impl S { fn f(&self) {} }
+fn f() { (&&S).f(); }"#,
+        );
+    }
+
+    #[test]
+    fn unqualify_method_call_parens_around_receiver() {
+        check_assist(
+            unqualify_method_call,
+            r#"
+//- minicore: add
+use core::ops::Add;
+fn f() { Add::$0add(1 + 1, 2); }"#,
+            r#"
+use core::ops::Add;
+fn f() { (1 + 1).add(2); }"#,
+        );
+    }
+
+    #[test]
+    fn unqualify_method_call_not_applicable_when_shadowed() {
+        cov_mark::check!(unqualify_method_call_shadowed);
+        check_assist_not_applicable(
+            unqualify_method_call,
+            r#"
+mod m {
+    pub trait Tr { fn f(&self) {} }
+    impl Tr for super::S {}
+}
+struct S;
+impl S { fn f(&self) {} }
+fn f() { m::Tr::$0f(&S); }"#,
+        );
+    }
 }
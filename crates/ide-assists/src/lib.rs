@@ -101,6 +101,30 @@ pub fn assists(
     acc.finish()
 }
 
+/// Returns the paths the `qualify_path` assist would offer for the unresolved
+/// name at the given position, without computing any edits.
+pub fn qualification_candidates(
+    db: &RootDatabase,
+    config: &AssistConfig,
+    range: FileRange,
+) -> Vec<hir::ModPath> {
+    let sema = Semantics::new(db);
+    let ctx = AssistContext::new(sema, config, range);
+    handlers::qualify_path::qualification_candidates(&ctx)
+}
+
+/// Returns the edits the `qualify_path` assist would offer for the unresolved
+/// name at the given position, one per candidate, without building assists.
+pub fn qualification_edits(
+    db: &RootDatabase,
+    config: &AssistConfig,
+    range: FileRange,
+) -> Vec<(TextRange, String)> {
+    let sema = Semantics::new(db);
+    let ctx = AssistContext::new(sema, config, range);
+    handlers::qualify_path::qualification_edits(&ctx)
+}
+
 mod handlers {
     use crate::{AssistContext, Assists};
 
@@ -177,7 +201,7 @@ mod handlers {
     mod number_representation;
     mod promote_local_to_const;
     mod pull_assignment_up;
-    mod qualify_path;
+    pub(crate) mod qualify_path;
     mod qualify_method_call;
     mod raw_string;
     mod remove_dbg;
@@ -195,6 +219,7 @@ mod handlers {
     mod replace_let_with_if_let;
     mod replace_qualified_name_with_use;
     mod replace_string_with_char;
+    mod replace_use_with_qualified_path;
     mod replace_turbofish_with_explicit_type;
     mod split_import;
     mod unmerge_match_arm;
@@ -282,6 +307,8 @@ mod handlers {
             number_representation::reformat_number_literal,
             pull_assignment_up::pull_assignment_up,
             promote_local_to_const::promote_local_to_const,
+            qualify_path::qualify_all_paths,
+            qualify_path::qualify_all_paths_through_imported_modules,
             qualify_path::qualify_path,
             qualify_method_call::qualify_method_call,
             raw_string::add_hash,
@@ -301,6 +328,8 @@ mod handlers {
             replace_method_eager_lazy::replace_with_lazy_method,
             replace_turbofish_with_explicit_type::replace_turbofish_with_explicit_type,
             replace_qualified_name_with_use::replace_qualified_name_with_use,
+            replace_qualified_name_with_use::replace_qualified_name_with_aliased_use,
+            replace_use_with_qualified_path::replace_use_with_qualified_path,
             replace_arith_op::replace_arith_with_wrapping,
             replace_arith_op::replace_arith_with_checked,
             replace_arith_op::replace_arith_with_saturating,
@@ -2,21 +2,22 @@ mod generated;
 #[cfg(not(feature = "in-rust-tree"))]
 mod sourcegen;
 
-use expect_test::expect;
+use expect_test::{expect, Expect};
 use hir::Semantics;
 use ide_db::{
+    assists::{AssistId, GroupLabel},
     base_db::{fixture::WithFixture, FileId, FileRange, SourceDatabaseExt},
     imports::insert_use::{ImportGranularity, InsertUseConfig},
-    source_change::FileSystemEdit,
+    source_change::{FileSystemEdit, SourceChange},
     RootDatabase, SnippetCap,
 };
 use stdx::{format_to, trim_indent};
-use syntax::TextRange;
+use syntax::{TextRange, TextSize};
 use test_utils::{assert_eq_text, extract_offset};
 
 use crate::{
-    assists, handlers::Handler, Assist, AssistConfig, AssistContext, AssistKind,
-    AssistResolveStrategy, Assists, SingleResolve,
+    assists, handlers::Handler, qualification_candidates, qualification_edits, Assist,
+    AssistConfig, AssistContext, AssistKind, AssistResolveStrategy, Assists, SingleResolve,
 };
 
 pub(crate) const TEST_CONFIG: AssistConfig = AssistConfig {
@@ -31,6 +32,10 @@ pub(crate) const TEST_CONFIG: AssistConfig = AssistConfig {
     },
     prefer_no_std: false,
     assist_emit_must_use: false,
+    max_width: None,
+    qualify_shortest_path_only: false,
+    qualify_imported_path_start: false,
+    qualify_trait_methods: true,
 };
 
 pub(crate) const TEST_CONFIG_NO_SNIPPET_CAP: AssistConfig = AssistConfig {
@@ -45,6 +50,10 @@ pub(crate) const TEST_CONFIG_NO_SNIPPET_CAP: AssistConfig = AssistConfig {
     },
     prefer_no_std: false,
     assist_emit_must_use: false,
+    max_width: None,
+    qualify_shortest_path_only: false,
+    qualify_imported_path_start: false,
+    qualify_trait_methods: true,
 };
 
 pub(crate) fn with_single_file(text: &str) -> (RootDatabase, FileId) {
@@ -57,6 +66,32 @@ pub(crate) fn check_assist(assist: Handler, ra_fixture_before: &str, ra_fixture_
     check(assist, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), None);
 }
 
+#[track_caller]
+pub(crate) fn check_assist_with_config(
+    assist: Handler,
+    config: AssistConfig,
+    ra_fixture_before: &str,
+    ra_fixture_after: &str,
+) {
+    let ra_fixture_after = trim_indent(ra_fixture_after);
+    check_with_config(
+        config,
+        assist,
+        ra_fixture_before,
+        ExpectedResult::After(&ra_fixture_after),
+        None,
+    );
+}
+
+#[track_caller]
+pub(crate) fn check_assist_not_applicable_with_config(
+    assist: Handler,
+    config: AssistConfig,
+    ra_fixture: &str,
+) {
+    check_with_config(config, assist, ra_fixture, ExpectedResult::NotApplicable, None);
+}
+
 #[track_caller]
 pub(crate) fn check_assist_no_snippet_cap(
     assist: Handler,
@@ -85,6 +120,57 @@ pub(crate) fn check_assist_by_label(
     check(assist, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), Some(label));
 }
 
+/// Checks the labels of all assists offered by the handler, prefixed with their group, if any.
+#[track_caller]
+pub(crate) fn check_assist_labels(assist: Handler, ra_fixture: &str, expect: Expect) {
+    check_assist_labels_with_config(assist, TEST_CONFIG, ra_fixture, expect)
+}
+
+#[track_caller]
+pub(crate) fn check_assist_labels_with_config(
+    assist: Handler,
+    config: AssistConfig,
+    ra_fixture: &str,
+    expect: Expect,
+) {
+    let (db, file_id, range_or_offset) = RootDatabase::with_range_or_offset(ra_fixture);
+    let frange = FileRange { file_id, range: range_or_offset.into() };
+    let sema = Semantics::new(&db);
+    let ctx = AssistContext::new(sema, &config, frange);
+    let mut acc = Assists::new(&ctx, AssistResolveStrategy::None);
+    assist(&mut acc, &ctx);
+
+    let mut labels = String::new();
+    for assist in acc.finish() {
+        match &assist.group {
+            Some(group) => format_to!(labels, "{}: {}\n", group.0, assist.label),
+            None => format_to!(labels, "{}\n", assist.label),
+        }
+    }
+    expect.assert_eq(&labels);
+}
+
+/// Checks the result of every assist offered by the handler, each preceded by its label, to see
+/// the whole menu at once.
+#[track_caller]
+pub(crate) fn check_assist_with_all_candidates(assist: Handler, ra_fixture: &str, expect: Expect) {
+    let (db, file_id, range_or_offset) = RootDatabase::with_range_or_offset(ra_fixture);
+    let frange = FileRange { file_id, range: range_or_offset.into() };
+    let sema = Semantics::new(&db);
+    let ctx = AssistContext::new(sema, &TEST_CONFIG, frange);
+    let mut acc = Assists::new(&ctx, AssistResolveStrategy::All);
+    assist(&mut acc, &ctx);
+
+    let mut buf = String::new();
+    for assist in acc.finish() {
+        format_to!(buf, "// {}\n", assist.label);
+        if let Some(source_change) = assist.source_change {
+            buf.push_str(&render_source_change(&db, source_change));
+        }
+    }
+    expect.assert_eq(&buf);
+}
+
 // FIXME: instead of having a separate function here, maybe use
 // `extract_ranges` and mark the target as `<target> </target>` in the
 // fixture?
@@ -187,41 +273,7 @@ fn check_with_config(
                 .source_change
                 .filter(|it| !it.source_file_edits.is_empty() || !it.file_system_edits.is_empty())
                 .expect("Assist did not contain any source changes");
-            let skip_header = source_change.source_file_edits.len() == 1
-                && source_change.file_system_edits.len() == 0;
-
-            let mut buf = String::new();
-            for (file_id, edit) in source_change.source_file_edits {
-                let mut text = db.file_text(file_id).as_ref().to_owned();
-                edit.apply(&mut text);
-                if !skip_header {
-                    let sr = db.file_source_root(file_id);
-                    let sr = db.source_root(sr);
-                    let path = sr.path_for_file(&file_id).unwrap();
-                    format_to!(buf, "//- {}\n", path)
-                }
-                buf.push_str(&text);
-            }
-
-            for file_system_edit in source_change.file_system_edits {
-                let (dst, contents) = match file_system_edit {
-                    FileSystemEdit::CreateFile { dst, initial_contents } => (dst, initial_contents),
-                    FileSystemEdit::MoveFile { src, dst } => {
-                        (dst, db.file_text(src).as_ref().to_owned())
-                    }
-                    FileSystemEdit::MoveDir { src, src_id, dst } => {
-                        // temporary placeholder for MoveDir since we are not using MoveDir in ide assists yet.
-                        (dst, format!("{src_id:?}\n{src:?}"))
-                    }
-                };
-                let sr = db.file_source_root(dst.anchor);
-                let sr = db.source_root(sr);
-                let mut base = sr.path_for_file(&dst.anchor).unwrap().clone();
-                base.pop();
-                let created_file_path = base.join(&dst.path).unwrap();
-                format_to!(buf, "//- {}\n", created_file_path);
-                buf.push_str(&contents);
-            }
+            let buf = render_source_change(&db, source_change);
 
             assert_eq_text!(after, &buf);
         }
@@ -244,6 +296,46 @@ fn check_with_config(
     };
 }
 
+/// Renders the files touched by `source_change` after applying it, with a header naming each
+/// file unless a single one is edited.
+fn render_source_change(db: &RootDatabase, source_change: SourceChange) -> String {
+    let skip_header =
+        source_change.source_file_edits.len() == 1 && source_change.file_system_edits.is_empty();
+
+    let mut buf = String::new();
+    for (file_id, edit) in source_change.source_file_edits {
+        let mut text = db.file_text(file_id).as_ref().to_owned();
+        edit.apply(&mut text);
+        if !skip_header {
+            let sr = db.file_source_root(file_id);
+            let sr = db.source_root(sr);
+            let path = sr.path_for_file(&file_id).unwrap();
+            format_to!(buf, "//- {}\n", path)
+        }
+        buf.push_str(&text);
+    }
+
+    for file_system_edit in source_change.file_system_edits {
+        let (dst, contents) = match file_system_edit {
+            FileSystemEdit::CreateFile { dst, initial_contents } => (dst, initial_contents),
+            FileSystemEdit::MoveFile { src, dst } => (dst, db.file_text(src).as_ref().to_owned()),
+            FileSystemEdit::MoveDir { src, src_id, dst } => {
+                // temporary placeholder for MoveDir since we are not using MoveDir in ide assists yet.
+                (dst, format!("{src_id:?}\n{src:?}"))
+            }
+        };
+        let sr = db.file_source_root(dst.anchor);
+        let sr = db.source_root(sr);
+        let mut base = sr.path_for_file(&dst.anchor).unwrap().clone();
+        base.pop();
+        let created_file_path = base.join(&dst.path).unwrap();
+        format_to!(buf, "//- {}\n", created_file_path);
+        buf.push_str(&contents);
+    }
+
+    buf
+}
+
 fn labels(assists: &[Assist]) -> String {
     let mut labels = assists
         .iter()
@@ -303,6 +395,88 @@ pub fn test_some_range(a: int) -> bool {
     .assert_eq(&expected);
 }
 
+#[test]
+fn assist_group_dedups_identical_labels() {
+    fn qualify_via_reexports(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+        let group = GroupLabel("Qualify Foo".to_owned());
+        let range = ctx.selection_trimmed();
+        // the second candidate reads the same as the first one, the third differs
+        for path in ["foo::Foo", "foo::Foo", "baz::Foo"] {
+            acc.add_group(
+                &group,
+                AssistId("qualify_path", AssistKind::QuickFix),
+                format!("Qualify as `{path}`"),
+                range,
+                |builder| builder.replace(range, path),
+            );
+        }
+        Some(())
+    }
+
+    let (db, frange) = RootDatabase::with_range("fn f() { $0Foo$0; }");
+    let sema = Semantics::new(&db);
+    let ctx = AssistContext::new(sema, &TEST_CONFIG, frange);
+    for resolve in [AssistResolveStrategy::None, AssistResolveStrategy::All] {
+        let mut acc = Assists::new(&ctx, resolve);
+        qualify_via_reexports(&mut acc, &ctx);
+        let assists = acc.finish();
+        let labels: Vec<_> = assists.iter().map(|it| it.label.to_string()).collect();
+        assert_eq!(labels, ["Qualify as `foo::Foo`", "Qualify as `baz::Foo`"]);
+    }
+}
+
+#[test]
+fn qualification_candidates_lists_all_paths() {
+    let (db, position) = RootDatabase::with_position(
+        r#"
+mod foo {
+    pub struct Bar;
+}
+mod baz {
+    pub mod qux {
+        pub struct Bar;
+    }
+}
+
+fn main() {
+    Bar$0;
+}
+"#,
+    );
+    let frange = FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) };
+
+    let candidates: Vec<String> = qualification_candidates(&db, &TEST_CONFIG, frange)
+        .iter()
+        .map(|path| path.to_string())
+        .collect();
+    assert_eq!(candidates, ["baz::qux::Bar", "foo::Bar"]);
+}
+
+#[test]
+fn qualification_edits_replace_the_name() {
+    let (db, position) = RootDatabase::with_position(
+        r#"
+mod foo {
+    pub struct Bar;
+}
+mod baz {
+    pub mod qux {
+        pub struct Bar;
+    }
+}
+
+fn main() {
+    Bar$0;
+}
+"#,
+    );
+    let frange = FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) };
+
+    let edits = qualification_edits(&db, &TEST_CONFIG, frange);
+    let name = TextRange::at(position.offset - TextSize::from(3), TextSize::from(3));
+    assert_eq!(edits, [(name, "baz::qux::Bar".to_owned()), (name, "foo::Bar".to_owned())]);
+}
+
 #[test]
 fn assist_filter_works() {
     let (db, frange) = RootDatabase::with_range(
@@ -389,6 +563,7 @@ pub fn test_some_range(a: int) -> bool {
                 target: 59..60,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_variable_assist);
@@ -405,6 +580,7 @@ pub fn test_some_range(a: int) -> bool {
                 target: 59..60,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_function_assist);
@@ -435,6 +611,7 @@ pub fn test_some_range(a: int) -> bool {
                 target: 59..60,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_variable_assist);
@@ -451,6 +628,7 @@ pub fn test_some_range(a: int) -> bool {
                 target: 59..60,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_function_assist);
@@ -502,6 +680,7 @@ pub fn test_some_range(a: int) -> bool {
                     },
                 ),
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_variable_assist);
@@ -518,6 +697,7 @@ pub fn test_some_range(a: int) -> bool {
                 target: 59..60,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_function_assist);
@@ -561,6 +741,7 @@ pub fn test_some_range(a: int) -> bool {
                     },
                 ),
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_variable_assist);
@@ -598,6 +779,7 @@ pub fn test_some_range(a: int) -> bool {
                     },
                 ),
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&extract_into_function_assist);
@@ -1966,6 +1966,46 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_qualify_all_paths() {
+    check_doc_test(
+        "qualify_all_paths",
+        r#####"
+fn main() {
+    let map = HashMap$0::new();
+    let set = HashSet::new();
+}
+pub mod std { pub mod collections { pub struct HashMap { } pub struct HashSet { } } }
+"#####,
+        r#####"
+fn main() {
+    let map = std::collections::HashMap::new();
+    let set = std::collections::HashSet::new();
+}
+pub mod std { pub mod collections { pub struct HashMap { } pub struct HashSet { } } }
+"#####,
+    )
+}
+
+#[test]
+fn doctest_qualify_all_paths_through_imported_modules() {
+    check_doc_test(
+        "qualify_all_paths_through_imported_modules",
+        r#####"
+use std::fmt;
+
+fn write(_: &mut Formatter$0, _: &dyn Write) {}
+pub mod std { pub mod fmt { pub struct Formatter; pub trait Write {} } pub mod io { pub trait Write {} } }
+"#####,
+        r#####"
+use std::fmt;
+
+fn write(_: &mut fmt::Formatter, _: &dyn fmt::Write) {}
+pub mod std { pub mod fmt { pub struct Formatter; pub trait Write {} } pub mod io { pub trait Write {} } }
+"#####,
+    )
+}
+
 #[test]
 fn doctest_qualify_method_call() {
     check_doc_test(
@@ -2338,6 +2378,25 @@ fn handle(action: Action) {
     )
 }
 
+#[test]
+fn doctest_replace_qualified_name_with_aliased_use() {
+    check_doc_test(
+        "replace_qualified_name_with_aliased_use",
+        r#####"
+mod std { pub mod fmt { pub struct Formatter; } }
+struct Formatter;
+fn fmt(f: std::fmt::$0Formatter) {}
+"#####,
+        r#####"
+use std::fmt::Formatter as Formatter2;
+
+mod std { pub mod fmt { pub struct Formatter; } }
+struct Formatter;
+fn fmt(f: Formatter2) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_qualified_name_with_use() {
     check_doc_test(
@@ -2412,6 +2471,24 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_replace_use_with_qualified_path() {
+    check_doc_test(
+        "replace_use_with_qualified_path",
+        r#####"
+mod std { pub mod collections { pub struct HashMap<K, V>(K, V); } }
+use std::collections::HashMap$0;
+
+fn process(map: HashMap<String, String>) {}
+"#####,
+        r#####"
+mod std { pub mod collections { pub struct HashMap<K, V>(K, V); } }
+
+fn process(map: std::collections::HashMap<String, String>) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_with_eager_method() {
     check_doc_test(
@@ -2642,6 +2719,8 @@ fn main() {
 mod std { pub mod ops { pub trait Add { fn add(self, _: Self) {} } impl Add for i32 {} } }
 "#####,
         r#####"
+use std::ops::Add;
+
 fn main() {
     1.add(2);
 }
@@ -18,7 +18,10 @@ pub struct Assist {
     pub label: Label,
     pub group: Option<GroupLabel>,
     /// Target ranges are used to sort assists: the smaller the target range,
-    /// the more specific assist is, and so it should be sorted first.
+    /// the more specific assist is, and so it should be sorted first. It is
+    /// also sent to clients supporting the `codeActionTarget` extension, to
+    /// highlight when previewing the assist, so it need not coincide with the
+    /// range of the edit.
     pub target: TextRange,
     /// Computing source change sometimes is much more costly then computing the
     /// other fields. Additionally, the actual change is not required to show
@@ -30,6 +33,9 @@ pub struct Assist {
     /// structure, such as a diagnostic.
     pub source_change: Option<SourceChange>,
     pub trigger_signature_help: bool,
+    /// Codes of the compiler diagnostics the assist fixes, so that clients can
+    /// link it to them.
+    pub diagnostic_codes: &'static [&'static str],
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1,6 +1,6 @@
 //! Look up accessible paths for items.
 use hir::{
-    AsAssocItem, AssocItem, AssocItemContainer, Crate, ItemInNs, ModPath, Module, ModuleDef,
+    AsAssocItem, AssocItem, AssocItemContainer, Crate, ItemInNs, ModPath, Module, ModuleDef, Name,
     PathResolution, PrefixKind, ScopeDef, Semantics, SemanticsScope, Type,
 };
 use itertools::Itertools;
@@ -208,6 +208,10 @@ impl ImportAssets {
         &self.import_candidate
     }
 
+    pub fn module_with_candidate(&self) -> Module {
+        self.module_with_candidate
+    }
+
     pub fn search_for_imports(
         &self,
         sema: &Semantics<'_, RootDatabase>,
@@ -250,13 +254,26 @@ impl ImportAssets {
 
         let scope_definitions = self.scope_definitions(sema);
         let mod_path = |item| {
-            get_mod_path(
-                sema.db,
-                item_for_path_search(sema.db, item)?,
-                &self.module_with_candidate,
-                prefixed,
-                prefer_no_std,
-            )
+            let item = item_for_path_search(sema.db, item)?;
+            let path =
+                get_mod_path(sema.db, item, &self.module_with_candidate, prefixed, prefer_no_std)?;
+            match (path.as_ident(), item_name(sema.db, item)) {
+                // the item is in scope under a `use` alias only, so go through its module instead
+                (Some(alias), Some(name)) if *alias != name => {
+                    let module = item.as_module_def().and_then(|it| it.module(sema.db));
+                    let Some(module) = module else { return Some(path) };
+                    let mut path = get_mod_path(
+                        sema.db,
+                        ItemInNs::Types(module.into()),
+                        &self.module_with_candidate,
+                        prefixed,
+                        prefer_no_std,
+                    )?;
+                    path.push_segment(name);
+                    Some(path)
+                }
+                _ => Some(path),
+            }
         };
 
         let krate = self.module_with_candidate.krate();
@@ -278,17 +295,22 @@ impl ImportAssets {
         }
         .into_iter()
         .filter(|import| import.import_path.len() > 1)
-        .filter(|import| !scope_definitions.contains(&ScopeDef::from(import.item_to_import)))
+        // an item in scope under another name, through a `use` alias, is still to be imported
+        .filter(|import| {
+            import.import_path.segments().last().map_or(true, |name| {
+                !scope_definitions.contains(&(name.clone(), ScopeDef::from(import.item_to_import)))
+            })
+        })
         .sorted_by(|a, b| a.import_path.cmp(&b.import_path))
         .collect()
     }
 
-    fn scope_definitions(&self, sema: &Semantics<'_, RootDatabase>) -> FxHashSet<ScopeDef> {
+    fn scope_definitions(&self, sema: &Semantics<'_, RootDatabase>) -> FxHashSet<(Name, ScopeDef)> {
         let _p = profile::span("import_assets::scope_definitions");
         let mut scope_definitions = FxHashSet::default();
         if let Some(scope) = sema.scope(&self.candidate_node) {
-            scope.process_all_names(&mut |_, scope_def| {
-                scope_definitions.insert(scope_def);
+            scope.process_all_names(&mut |name, scope_def| {
+                scope_definitions.insert((name, scope_def));
             });
         }
         scope_definitions
@@ -594,7 +616,9 @@ impl ImportCandidate {
     }
 
     fn for_regular_path(sema: &Semantics<'_, RootDatabase>, path: &ast::Path) -> Option<Self> {
+        // nothing to import for a resolved path, don't pay for searching the candidates
         if sema.resolve_path(path).is_some() {
+            cov_mark::hit!(import_assets_resolved_path);
             return None;
         }
         path_import_candidate(
@@ -657,6 +681,12 @@ fn path_import_candidate(
                     assoc_item_name: name,
                 })
             }
+            Some(PathResolution::SelfType(impl_)) => {
+                ImportCandidate::TraitAssocItem(TraitImportCandidate {
+                    receiver_ty: impl_.self_ty(sema.db),
+                    assoc_item_name: name,
+                })
+            }
             Some(PathResolution::Def(ModuleDef::TypeAlias(alias))) => {
                 let ty = alias.ty(sema.db);
                 if ty.as_adt().is_some() {
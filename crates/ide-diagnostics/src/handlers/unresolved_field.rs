@@ -63,6 +63,7 @@ fn method_fix(
             TextEdit::insert(range.end(), "()".to_owned()),
         )),
         trigger_signature_help: false,
+        diagnostic_codes: &[],
     }])
 }
 #[cfg(test)]
@@ -78,6 +78,7 @@ fn field_fix(
             (file_id, TextEdit::insert(range.end(), ")".to_owned())),
         ])),
         trigger_signature_help: false,
+        diagnostic_codes: &[],
     }])
 }
 
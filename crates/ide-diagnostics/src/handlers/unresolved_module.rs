@@ -118,6 +118,7 @@ mod baz {}
                                         },
                                     ),
                                     trigger_signature_help: false,
+                                    diagnostic_codes: [],
                                 },
                                 Assist {
                                     id: AssistId(
@@ -145,6 +146,7 @@ mod baz {}
                                         },
                                     ),
                                     trigger_signature_help: false,
+                                    diagnostic_codes: [],
                                 },
                             ],
                         ),
@@ -328,6 +328,7 @@ fn unresolved_fix(id: &'static str, label: &str, target: TextRange) -> Assist {
         target,
         source_change: None,
         trigger_signature_help: false,
+        diagnostic_codes: &[],
     }
 }
 
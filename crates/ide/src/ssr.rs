@@ -46,6 +46,7 @@ pub(crate) fn ssr_assists(
             target: comment_range,
             source_change,
             trigger_signature_help: false,
+            diagnostic_codes: &[],
         };
 
         ssr_assists.push(assist);
@@ -140,6 +141,7 @@ mod tests {
                     },
                 ),
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&apply_in_file_assist);
@@ -187,6 +189,7 @@ mod tests {
                     },
                 ),
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&apply_in_workspace_assist);
@@ -227,6 +230,7 @@ mod tests {
                 target: 10..21,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&apply_in_file_assist);
@@ -247,6 +251,7 @@ mod tests {
                 target: 10..21,
                 source_change: None,
                 trigger_signature_help: false,
+                diagnostic_codes: [],
             }
         "#]]
         .assert_debug_eq(&apply_in_workspace_assist);
@@ -2,21 +2,34 @@
 //! filter syntax and `tracing_appender` for non blocking output.
 
 use std::{
+    collections::VecDeque,
     fmt,
-    fs::File,
-    io::{self, Stderr},
-    sync::Arc,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, StderrLock, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError, RwLock,
+    },
+    thread,
+    time::Duration,
 };
 
+use crossbeam_channel::{Receiver, Sender, TrySendError};
 use rust_analyzer::Result;
-use tracing::{level_filters::LevelFilter, Event, Subscriber};
+use rustc_hash::FxHashMap;
+use tracing::{
+    level_filters::LevelFilter, span, subscriber::Interest, Event, Level, Metadata, Subscriber,
+};
 use tracing_log::NormalizeEvent;
 use tracing_subscriber::{
+    filter::Directive,
     fmt::{
-        format::Writer, writer::BoxMakeWriter, FmtContext, FormatEvent, FormatFields,
-        FormattedFields, MakeWriter,
+        format::Writer,
+        writer::{BoxMakeWriter, MutexGuardWriter},
+        FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter,
     },
-    layer::SubscriberExt,
+    layer::{Context, Layer, SubscriberExt},
     registry::LookupSpan,
     util::SubscriberInitExt,
     EnvFilter, Registry,
@@ -25,27 +38,715 @@ use tracing_tree::HierarchicalLayer;
 
 pub(crate) struct Logger {
     filter: EnvFilter,
-    file: Option<File>,
+    /// Whether the filter only depends on the target and the level of a record.
+    static_filter: bool,
+    /// The directives of the filter which failed to parse, and were ignored.
+    filter_warnings: Vec<String>,
+    /// The main output, stderr when not set.
+    writer: Option<Mutex<Box<dyn Write + Send>>>,
+    stderr: MakeWriterStderr,
+    flush_interval: Option<Duration>,
+    /// The buffered log files, see [`Logger::flush`].
+    buffered: Vec<Arc<Mutex<dyn Write + Send>>>,
+    queue: Option<(usize, Backpressure)>,
+    /// The queue of the log file, see [`Logger::flush`].
+    queue_writer: Option<MakeWriterQueue>,
+    format: LogFormat,
+    thread_names: bool,
+    locations: bool,
+    tag: Option<String>,
+    recent: Option<MakeWriterRecent>,
+    sinks: Option<MakeWriterSinks>,
+    /// Whether writing to the log file failed, and logging fell back to stderr.
+    file_errors: Arc<FileErrors>,
+}
+
+/// Locks stderr for each record, so that records logged concurrently don't
+/// interleave with each other or with other output to stderr.
+#[derive(Clone, Default)]
+struct MakeWriterStderr {
+    /// Stands in for stderr if set, see [`LoggerBuilder::stderr`].
+    stream: Option<Arc<Mutex<dyn Write + Send>>>,
 }
 
-struct MakeWriterStderr;
+enum StderrWriter<'a> {
+    Stderr(StderrLock<'static>),
+    Stream(MutexGuard<'a, dyn Write + Send + 'static>),
+}
 
 impl<'a> MakeWriter<'a> for MakeWriterStderr {
-    type Writer = Stderr;
+    type Writer = StderrWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        match &self.stream {
+            Some(stream) => {
+                StderrWriter::Stream(stream.lock().unwrap_or_else(PoisonError::into_inner))
+            }
+            None => StderrWriter::Stderr(io::stderr().lock()),
+        }
+    }
+}
+
+impl Write for StderrWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            StderrWriter::Stderr(it) => it.write(buf),
+            StderrWriter::Stream(it) => it.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            StderrWriter::Stderr(it) => it.flush(),
+            StderrWriter::Stream(it) => it.flush(),
+        }
+    }
+}
+
+/// The shortest interval buffered logs are flushed at, so that the flusher
+/// thread doesn't keep a core busy.
+const MIN_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Buffers writes to the log file, and flushes them from a background thread
+/// every `interval`, so that buffered records reach the disk in a timely
+/// manner without paying for a flush on every line.
+struct MakeWriterPeriodicFlush<W: Write> {
+    file: Arc<Mutex<BufWriter<W>>>,
+}
+
+impl<W: Write> Clone for MakeWriterPeriodicFlush<W> {
+    fn clone(&self) -> Self {
+        MakeWriterPeriodicFlush { file: self.file.clone() }
+    }
+}
+
+impl<W: Write + Send + 'static> MakeWriterPeriodicFlush<W> {
+    /// Flushes `file` every `interval`, and hands the outcome to `flushed`.
+    fn spawn(
+        file: W,
+        interval: Duration,
+        flushed: impl Fn(io::Result<()>) + Send + 'static,
+    ) -> MakeWriterPeriodicFlush<W> {
+        let file = Arc::new(Mutex::new(BufWriter::new(file)));
+        // Only hold a weak reference, so that the flusher exits once the
+        // writer itself is gone.
+        let weak = Arc::downgrade(&file);
+        thread::Builder::new()
+            .name("LogFlusher".to_owned())
+            .spawn(move || loop {
+                thread::sleep(interval);
+                match weak.upgrade() {
+                    Some(file) => {
+                        flushed(file.lock().unwrap_or_else(PoisonError::into_inner).flush());
+                    }
+                    None => break,
+                }
+            })
+            .expect("failed to spawn the log flusher thread");
+        MakeWriterPeriodicFlush { file }
+    }
+}
+
+impl<'a, W: Write + 'a> MakeWriter<'a> for MakeWriterPeriodicFlush<W> {
+    type Writer = MutexGuardWriter<'a, BufWriter<W>>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.file.make_writer()
+    }
+}
+
+/// What to do with a record when the queue of the log file is full, see
+/// [`LoggerBuilder::queue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Backpressure {
+    /// Waits for the writer thread to catch up.
+    Block,
+    /// Drops the record, the number of dropped records is logged later on.
+    Drop,
+}
+
+/// Hands the records over to a background thread which writes them to the
+/// log file, so that logging never waits for the disk, or for other threads
+/// writing to it.
+#[derive(Clone)]
+struct MakeWriterQueue {
+    /// `None` once the queue is closed, see [`MakeWriterQueue::close`].
+    sender: Arc<RwLock<Option<Sender<Vec<u8>>>>>,
+    backpressure: Backpressure,
+    dropped: Arc<AtomicUsize>,
+    writer_thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+}
+
+impl MakeWriterQueue {
+    /// Like [`MakeWriterPeriodicFlush::spawn`], the outcome of writing each
+    /// batch of records is handed to `flushed`.
+    fn spawn(
+        file: impl Write + Send + 'static,
+        capacity: usize,
+        backpressure: Backpressure,
+        flushed: impl Fn(io::Result<()>) + Send + 'static,
+    ) -> MakeWriterQueue {
+        let (sender, receiver) = crossbeam_channel::bounded(capacity);
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let writer_thread = {
+            let dropped = dropped.clone();
+            thread::Builder::new()
+                .name("LogWriter".to_owned())
+                .spawn(move || {
+                    MakeWriterQueue::drain(receiver, BufWriter::new(file), &dropped, flushed)
+                })
+                .expect("failed to spawn the log writer thread")
+        };
+        MakeWriterQueue {
+            sender: Arc::new(RwLock::new(Some(sender))),
+            backpressure,
+            dropped,
+            writer_thread: Arc::new(Mutex::new(Some(writer_thread))),
+        }
+    }
+
+    /// Closes the queue and waits for the writer thread to write out the
+    /// records still in it. Records logged afterwards fail to be written.
+    fn close(&self) {
+        self.sender.write().unwrap_or_else(PoisonError::into_inner).take();
+        let writer_thread =
+            self.writer_thread.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(writer_thread) = writer_thread {
+            let _ = writer_thread.join();
+        }
+    }
+
+    /// Writes the records until all senders are gone, flushing whenever the
+    /// queue runs empty.
+    fn drain(
+        receiver: Receiver<Vec<u8>>,
+        mut file: BufWriter<impl Write>,
+        dropped: &AtomicUsize,
+        flushed: impl Fn(io::Result<()>),
+    ) {
+        while let Ok(record) = receiver.recv() {
+            let mut result = file.write_all(&record);
+            for record in receiver.try_iter() {
+                result = result.and(file.write_all(&record));
+            }
+            let dropped = dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                result = result.and(writeln!(
+                    file,
+                    "[WARN rust_analyzer::logger] dropped {dropped} records, the log queue was full"
+                ));
+            }
+            flushed(result.and(file.flush()));
+        }
+    }
+}
+
+impl Write for &MakeWriterQueue {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The fmt layer writes each formatted record with a single call.
+        let record = buf.to_vec();
+        let sender = self.sender.read().unwrap_or_else(PoisonError::into_inner);
+        let Some(sender) = &*sender else {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the log queue is closed"));
+        };
+        let sent = match self.backpressure {
+            Backpressure::Block => sender.send(record).is_ok(),
+            Backpressure::Drop => match sender.try_send(record) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            },
+        };
+        if !sent {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the log writer thread is gone"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for MakeWriterQueue {
+    type Writer = &'a MakeWriterQueue;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
+
+/// The number of consecutive failed writes after which the log file is given up on.
+const MAX_FILE_ERRORS: usize = 3;
+
+#[derive(Debug, Default)]
+struct FileErrors {
+    /// The number of consecutive failed writes to the log file.
+    consecutive: AtomicUsize,
+    fell_back: AtomicBool,
+}
+
+impl FileErrors {
+    /// Counts a failed write to the log file, and returns the number of
+    /// failures in a row if logging falls back to stderr because of it.
+    fn failed(&self) -> Option<usize> {
+        let consecutive = self.consecutive.fetch_add(1, Ordering::Relaxed) + 1;
+        let falls_back =
+            consecutive >= MAX_FILE_ERRORS && !self.fell_back.swap(true, Ordering::Relaxed);
+        falls_back.then_some(consecutive)
+    }
+
+    /// Counts the outcome of flushing the buffered log file, which is the
+    /// only time buffered writes report their errors.
+    fn flushed(&self, result: io::Result<()>, stderr: &MakeWriterStderr) {
+        match result {
+            Ok(()) => self.consecutive.store(0, Ordering::Relaxed),
+            Err(err) => {
+                if let Some(consecutive) = self.failed() {
+                    let _ = warn_fallback(&mut stderr.make_writer(), consecutive, &err);
+                }
+            }
+        }
+    }
+}
+
+fn warn_fallback(fallback: &mut impl Write, consecutive: usize, err: &io::Error) -> io::Result<()> {
+    writeln!(
+        fallback,
+        "[WARN rust_analyzer::logger] failed to write to the log file {consecutive} times \
+         in a row ({err}), logging to stderr instead"
+    )
+}
+
+/// Writes to the log file until writing to it fails repeatedly, for example
+/// because the disk is full, and to the `fallback` (stderr) from then on, so
+/// that the logs aren't silently lost for the rest of the session.
+struct MakeWriterFallback<M, F> {
+    file: M,
+    fallback: F,
+    errors: Arc<FileErrors>,
+    /// Whether writes only reach a buffer, so that only flushing tells if the
+    /// log file works, see [`FileErrors::flushed`].
+    buffered: bool,
+}
+
+impl<M> MakeWriterFallback<M, MakeWriterStderr> {
+    fn new(file: M, stderr: MakeWriterStderr, errors: Arc<FileErrors>) -> Self {
+        MakeWriterFallback { file, fallback: stderr, errors, buffered: false }
+    }
+
+    fn new_buffered(file: M, stderr: MakeWriterStderr, errors: Arc<FileErrors>) -> Self {
+        MakeWriterFallback { file, fallback: stderr, errors, buffered: true }
+    }
+}
+
+enum FallbackWriter<'a, W, F: MakeWriter<'a>> {
+    File { writer: W, fallback: &'a F, errors: &'a FileErrors, buffered: bool },
+    Fallback(F::Writer),
+}
+
+impl<'a, M, F> MakeWriter<'a> for MakeWriterFallback<M, F>
+where
+    M: MakeWriter<'a>,
+    F: MakeWriter<'a> + 'a,
+{
+    type Writer = FallbackWriter<'a, M::Writer, F>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        if self.errors.fell_back.load(Ordering::Relaxed) {
+            FallbackWriter::Fallback(self.fallback.make_writer())
+        } else {
+            FallbackWriter::File {
+                writer: self.file.make_writer(),
+                fallback: &self.fallback,
+                errors: &self.errors,
+                buffered: self.buffered,
+            }
+        }
+    }
+}
+
+impl<'a, W: Write, F: MakeWriter<'a>> Write for FallbackWriter<'a, W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (writer, fallback, errors, buffered) = match self {
+            FallbackWriter::File { writer, fallback, errors, buffered } => {
+                (writer, fallback, errors, *buffered)
+            }
+            FallbackWriter::Fallback(writer) => return writer.write(buf),
+        };
+        let err = match writer.write_all(buf) {
+            Ok(()) => {
+                if !buffered {
+                    errors.consecutive.store(0, Ordering::Relaxed);
+                }
+                return Ok(buf.len());
+            }
+            Err(err) => err,
+        };
+        let Some(consecutive) = errors.failed() else { return Err(err) };
+        // Warn once, and keep the record which failed to be written.
+        let mut fallback = fallback.make_writer();
+        warn_fallback(&mut fallback, consecutive, &err)?;
+        fallback.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FallbackWriter::File { writer, .. } => writer.flush(),
+            FallbackWriter::Fallback(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Keeps the last `capacity` log records in memory, independently of where
+/// the logs are written to, so that they can be dumped on a crash.
+#[derive(Clone)]
+struct MakeWriterRecent {
+    records: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl MakeWriterRecent {
+    fn new(capacity: usize) -> MakeWriterRecent {
+        MakeWriterRecent { records: Arc::new(Mutex::new(VecDeque::new())), capacity }
+    }
+}
+
+impl Write for &MakeWriterRecent {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The fmt layer writes each formatted record with a single call.
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        records.push_back(String::from_utf8_lossy(buf).trim_end().to_owned());
+        while records.len() > self.capacity {
+            records.pop_front();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for MakeWriterRecent {
+    type Writer = &'a MakeWriterRecent;
 
     fn make_writer(&'a self) -> Self::Writer {
-        io::stderr()
+        self
+    }
+}
+
+/// Writes each record to all the files whose level filter admits it, for
+/// example warnings and errors to one file and everything to another.
+#[derive(Clone)]
+struct MakeWriterSinks {
+    sinks: Arc<Vec<(LevelFilter, Arc<Mutex<BufWriter<File>>>)>>,
+    /// Whether the sinks are flushed after each record, rather than by a
+    /// background thread.
+    flush_each: bool,
+}
+
+impl MakeWriterSinks {
+    fn new(sinks: Vec<(LevelFilter, File)>, flush_interval: Option<Duration>) -> MakeWriterSinks {
+        let sinks = sinks
+            .into_iter()
+            .map(|(level, file)| {
+                let file = match flush_interval {
+                    // there is nothing to fall back to for the additional sinks
+                    Some(interval) => MakeWriterPeriodicFlush::spawn(file, interval, drop).file,
+                    None => Arc::new(Mutex::new(BufWriter::new(file))),
+                };
+                (level, file)
+            })
+            .collect();
+        MakeWriterSinks { sinks: Arc::new(sinks), flush_each: flush_interval.is_none() }
+    }
+}
+
+struct SinksWriter<'a> {
+    sinks: &'a MakeWriterSinks,
+    /// The level of the record being written, `None` writes to all sinks.
+    level: Option<Level>,
+}
+
+impl Write for SinksWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (filter, file) in self.sinks.sinks.iter() {
+            if self.level.map_or(false, |level| level > *filter) {
+                continue;
+            }
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            file.write_all(buf)?;
+            if self.sinks.flush_each {
+                file.flush()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for (_, file) in self.sinks.sinks.iter() {
+            file.lock().unwrap_or_else(PoisonError::into_inner).flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for MakeWriterSinks {
+    type Writer = SinksWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SinksWriter { sinks: self, level: None }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        SinksWriter { sinks: self, level: Some(*meta.level()) }
+    }
+}
+
+/// How each log line is laid out, see [`LoggerBuilder::format`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum LogFormat {
+    /// `[LEVEL target] span{fields}: message`
+    #[default]
+    Full,
+    /// `[LEVEL] message`, without the target and the spans.
+    Compact,
+}
+
+/// Configures a [`Logger`], see [`Logger::builder`].
+#[derive(Default)]
+pub(crate) struct LoggerBuilder {
+    writer: Option<Box<dyn Write + Send>>,
+    stderr: MakeWriterStderr,
+    filter: Option<String>,
+    flush_interval: Option<Duration>,
+    queue: Option<(usize, Backpressure)>,
+    format: LogFormat,
+    thread_names: bool,
+    locations: bool,
+    tag: Option<String>,
+    recent_records: Option<usize>,
+    sinks: Vec<(LevelFilter, File)>,
+}
+
+impl LoggerBuilder {
+    /// Logs to `writer` instead of stderr, for example to a socket.
+    pub(crate) fn writer(mut self, writer: impl Write + Send + 'static) -> LoggerBuilder {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Writes what would go to stderr to `stream` instead.
+    #[cfg(test)]
+    fn stderr(mut self, stream: Arc<Mutex<dyn Write + Send>>) -> LoggerBuilder {
+        self.stderr = MakeWriterStderr { stream: Some(stream) };
+        self
+    }
+
+    /// Logs to `file` instead of stderr.
+    pub(crate) fn file(self, file: File) -> LoggerBuilder {
+        self.writer(file)
+    }
+
+    /// Logs to the file at `path`, which is created if needed and truncated
+    /// otherwise.
+    pub(crate) fn file_path(self, path: &Path) -> io::Result<LoggerBuilder> {
+        self.open_file(path, OpenOptions::new().write(true).create(true).truncate(true))
+    }
+
+    /// Like [`LoggerBuilder::file_path`], but keeps the existing contents of
+    /// the file and appends to them.
+    pub(crate) fn file_path_appending(self, path: &Path) -> io::Result<LoggerBuilder> {
+        self.open_file(path, OpenOptions::new().create(true).append(true))
+    }
+
+    fn open_file(self, path: &Path, options: &OpenOptions) -> io::Result<LoggerBuilder> {
+        let file = options.open(path).map_err(|err| {
+            io::Error::new(err.kind(), format!("failed to open log file {}: {err}", path.display()))
+        })?;
+        Ok(self.file(file))
+    }
+
+    /// Only logs the records admitted by `filter`, in `tracing_subscriber`
+    /// filter syntax. Without a filter, only errors are logged.
+    pub(crate) fn filter(mut self, filter: &str) -> LoggerBuilder {
+        self.filter = Some(filter.to_owned());
+        self
+    }
+
+    /// When `flush_interval` is set, writes to files are buffered and flushed
+    /// at most every `flush_interval`, otherwise every record is written to
+    /// the file right away. Intervals below 10ms are raised to 10ms.
+    pub(crate) fn buffering(mut self, flush_interval: Option<Duration>) -> LoggerBuilder {
+        self.flush_interval = flush_interval.map(|it| it.max(MIN_FLUSH_INTERVAL));
+        self
+    }
+
+    /// Writes to the log file from a background thread, which is handed the
+    /// records through a queue of `capacity` records. Takes precedence over
+    /// [`LoggerBuilder::buffering`], the thread flushes whenever it caught up.
+    /// A capacity of 0 is raised to 1, a queue without room would drop every
+    /// record with [`Backpressure::Drop`].
+    pub(crate) fn queue(mut self, capacity: usize, backpressure: Backpressure) -> LoggerBuilder {
+        self.queue = Some((capacity.max(1), backpressure));
+        self
+    }
+
+    /// Lays out each log line according to `format`, [`LogFormat::Full`] by
+    /// default.
+    pub(crate) fn format(mut self, format: LogFormat) -> LoggerBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Includes the name (or, for unnamed threads, the id) of the thread
+    /// which emitted the record in each log line.
+    pub(crate) fn thread_names(mut self, thread_names: bool) -> LoggerBuilder {
+        self.thread_names = thread_names;
+        self
+    }
+
+    /// Includes the source file and line of the log statement, where known,
+    /// after the target of each log line.
+    pub(crate) fn locations(mut self, locations: bool) -> LoggerBuilder {
+        self.locations = locations;
+        self
+    }
+
+    /// Prefixes each log line with `[tag] `, to tell apart the records of
+    /// several servers logging to the same file.
+    pub(crate) fn tag(mut self, tag: impl Into<String>) -> LoggerBuilder {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Keeps the last `capacity` records in memory, see [`Logger::recent`].
+    pub(crate) fn recent_records(mut self, capacity: usize) -> LoggerBuilder {
+        self.recent_records = Some(capacity);
+        self
+    }
+
+    /// Additionally writes the records admitted by `level` to `file`,
+    /// independently of the main log output.
+    pub(crate) fn sink(mut self, level: LevelFilter, file: File) -> LoggerBuilder {
+        self.sinks.push((level, file));
+        self
+    }
+
+    pub(crate) fn build(self) -> Logger {
+        let filter = self.filter.as_deref();
+        // Span and field directives make the verdict depend on more than the target.
+        let static_filter = filter.map_or(true, |it| !it.contains(['[', '{']));
+        let mut filter_warnings = Vec::new();
+        let filter = match filter {
+            Some(filter) => {
+                let directives: Vec<&str> = filter
+                    .split(',')
+                    .filter(|it| !it.is_empty())
+                    .filter(|it| match it.parse::<Directive>() {
+                        Ok(_) => true,
+                        Err(err) => {
+                            filter_warnings
+                                .push(format!("ignoring `{it}` in the log filter: {err}"));
+                            false
+                        }
+                    })
+                    .collect();
+                EnvFilter::new(directives.join(","))
+            }
+            None => EnvFilter::default(),
+        };
+        let sinks =
+            (!self.sinks.is_empty()).then(|| MakeWriterSinks::new(self.sinks, self.flush_interval));
+        let buffered = match &sinks {
+            Some(sinks) => sinks.sinks.iter().map(|(_, file)| file.clone() as _).collect(),
+            None => Vec::new(),
+        };
+
+        Logger {
+            filter,
+            static_filter,
+            filter_warnings,
+            writer: self.writer.map(Mutex::new),
+            stderr: self.stderr,
+            flush_interval: self.flush_interval,
+            buffered,
+            queue: self.queue,
+            queue_writer: None,
+            format: self.format,
+            thread_names: self.thread_names,
+            locations: self.locations,
+            tag: self.tag,
+            recent: self.recent_records.map(MakeWriterRecent::new),
+            sinks,
+            file_errors: Arc::default(),
+        }
+    }
+
+    /// Builds the logger and installs it as the global default, see
+    /// [`Logger::install`].
+    pub(crate) fn install(self) -> Result<Logger> {
+        let mut logger = self.build();
+        logger.install()?;
+        Ok(logger)
     }
 }
 
 impl Logger {
-    pub(crate) fn new(file: Option<File>, filter: Option<&str>) -> Logger {
-        let filter = filter.map_or(EnvFilter::default(), EnvFilter::new);
+    pub(crate) fn builder() -> LoggerBuilder {
+        LoggerBuilder::default()
+    }
+
+    /// Creates a new logger, prefer [`Logger::builder`] to configure anything
+    /// beyond the log file, the filter and the buffering.
+    #[allow(dead_code)]
+    pub(crate) fn new(
+        file: Option<File>,
+        filter: Option<&str>,
+        flush_interval: Option<Duration>,
+    ) -> Logger {
+        let mut builder = Logger::builder().buffering(flush_interval);
+        if let Some(file) = file {
+            builder = builder.file(file);
+        }
+        if let Some(filter) = filter {
+            builder = builder.filter(filter);
+        }
+        builder.build()
+    }
+
+    /// Flushes the buffered log files, and closes the queue of the log file
+    /// once its records are written. The installed logger is never dropped,
+    /// so this has to be called before exiting to not lose the last records.
+    pub(crate) fn flush(&self) {
+        for file in &self.buffered {
+            let _ = file.lock().unwrap_or_else(PoisonError::into_inner).flush();
+        }
+        if let Some(queue_writer) = &self.queue_writer {
+            queue_writer.close();
+        }
+    }
 
-        Logger { filter, file }
+    /// Returns the most recent log records, oldest first, if the logger was
+    /// configured to keep them.
+    pub(crate) fn recent(&self) -> Vec<String> {
+        match &self.recent {
+            Some(recent) => {
+                let records = recent.records.lock().unwrap_or_else(PoisonError::into_inner);
+                records.iter().cloned().collect()
+            }
+            None => Vec::new(),
+        }
     }
 
-    pub(crate) fn install(self) -> Result<()> {
+    /// Installs the logger as the global default. The logger itself stays
+    /// usable afterwards to access the [`Logger::recent`] records.
+    pub(crate) fn install(&mut self) -> Result<()> {
         // The meaning of CHALK_DEBUG I suspected is to tell chalk crates
         // (i.e. chalk-solve, chalk-ir, chalk-recursive) how to filter tracing
         // logs. But now we can only have just one filter, which means we have to
@@ -67,39 +768,214 @@ impl Logger {
             .with_indent_lines(true)
             .with_ansi(false)
             .with_indent_amount(2)
-            .with_writer(io::stderr);
+            .with_writer(self.stderr.clone());
 
-        let writer = match self.file {
-            Some(file) => BoxMakeWriter::new(Arc::new(file)),
-            None => BoxMakeWriter::new(io::stderr),
-        };
-        let ra_fmt_layer =
-            tracing_subscriber::fmt::layer().event_format(LoggerFormatter).with_writer(writer);
+        let ra_fmt_layer = self.fmt_layer();
+        let recent_layer = self.recent_layer();
+        let sinks_layer = self.sinks_layer();
+        let banner = self.banner();
+        let filter = std::mem::take(&mut self.filter);
 
         match chalk_level_dir {
             Some(val) => {
+                let filter = filter
+                    .add_directive(format!("chalk_solve={val}").parse()?)
+                    .add_directive(format!("chalk_ir={val}").parse()?)
+                    .add_directive(format!("chalk_recursive={val}").parse()?);
                 Registry::default()
-                    .with(
-                        self.filter
-                            .add_directive(format!("chalk_solve={val}").parse()?)
-                            .add_directive(format!("chalk_ir={val}").parse()?)
-                            .add_directive(format!("chalk_recursive={val}").parse()?),
-                    )
+                    .with(CachedFilter::new(filter, self.static_filter))
                     .with(ra_fmt_layer)
+                    .with(recent_layer)
+                    .with(sinks_layer)
                     .with(chalk_layer)
                     .init();
             }
             None => {
-                Registry::default().with(self.filter).with(ra_fmt_layer).init();
+                Registry::default()
+                    .with(CachedFilter::new(filter, self.static_filter))
+                    .with(ra_fmt_layer)
+                    .with(recent_layer)
+                    .with(sinks_layer)
+                    .init();
             }
         };
+        log_banner(&banner);
+        log_filter_warnings(&self.filter_warnings);
 
         Ok(())
     }
+
+    /// Describes the version and the configuration of the logger, so that the
+    /// logs are self-describing.
+    fn banner(&self) -> String {
+        let buffering = match (self.queue, self.flush_interval) {
+            (Some((capacity, _)), _) => format!("queued ({capacity} records)"),
+            (None, Some(interval)) => format!("flushed every {interval:?}"),
+            (None, None) => "off".to_owned(),
+        };
+        format!(
+            "rust-analyzer {} logging with filter `{}`, buffering {buffering}",
+            rust_analyzer::version(),
+            self.filter
+        )
+    }
+
+    fn formatter(&self) -> LoggerFormatter {
+        LoggerFormatter {
+            format: self.format,
+            thread_names: self.thread_names,
+            locations: self.locations,
+            tag: self.tag.clone(),
+        }
+    }
+
+    /// The main output, either to the log file or to stderr.
+    fn fmt_layer<S>(&mut self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let (stderr, errors) = (self.stderr.clone(), self.file_errors.clone());
+        let flushed = {
+            let (stderr, errors) = (stderr.clone(), errors.clone());
+            move |result| errors.flushed(result, &stderr)
+        };
+        let writer =
+            self.writer.take().map(|it| it.into_inner().unwrap_or_else(PoisonError::into_inner));
+        let writer = match (writer, self.queue, self.flush_interval) {
+            (Some(writer), Some((capacity, backpressure)), _) => {
+                let writer = MakeWriterQueue::spawn(writer, capacity, backpressure, flushed);
+                self.queue_writer = Some(writer.clone());
+                BoxMakeWriter::new(MakeWriterFallback::new_buffered(writer, stderr, errors))
+            }
+            (Some(writer), None, Some(interval)) => {
+                let writer = MakeWriterPeriodicFlush::spawn(writer, interval, flushed);
+                self.buffered.push(writer.file.clone());
+                BoxMakeWriter::new(MakeWriterFallback::new_buffered(writer, stderr, errors))
+            }
+            (Some(writer), None, None) => {
+                BoxMakeWriter::new(MakeWriterFallback::new(Mutex::new(writer), stderr, errors))
+            }
+            (None, ..) => BoxMakeWriter::new(stderr),
+        };
+        tracing_subscriber::fmt::layer().event_format(self.formatter()).with_writer(writer)
+    }
+
+    fn recent_layer<S>(&self) -> Option<impl Layer<S>>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let writer = self.recent.clone()?;
+        let layer =
+            tracing_subscriber::fmt::layer().event_format(self.formatter()).with_writer(writer);
+        Some(layer)
+    }
+
+    fn sinks_layer<S>(&self) -> Option<impl Layer<S>>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let writer = self.sinks.clone()?;
+        let layer =
+            tracing_subscriber::fmt::layer().event_format(self.formatter()).with_writer(writer);
+        Some(layer)
+    }
+}
+
+/// Logs the [`Logger::banner`] as info, so only if the filter admits it.
+fn log_banner(banner: &str) {
+    tracing::info!(target: "rust_analyzer::logger", "{banner}");
 }
 
-#[derive(Debug)]
-struct LoggerFormatter;
+/// Logs the directives of the filter which failed to parse as warnings, or
+/// writes them to stderr if the filter itself doesn't admit warnings.
+fn log_filter_warnings(warnings: &[String]) {
+    for warning in warnings {
+        if tracing::enabled!(target: "rust_analyzer::logger", Level::WARN) {
+            tracing::warn!(target: "rust_analyzer::logger", "{warning}");
+        } else {
+            eprintln!("{warning}");
+        }
+    }
+}
+
+/// Wraps an [`EnvFilter`], remembering its verdict for each target and level,
+/// so that repeated records don't have to be matched against all directives.
+/// Only caches when the filter has no span or field directives.
+struct CachedFilter {
+    filter: EnvFilter,
+    cache: Option<RwLock<FxHashMap<String, [Option<bool>; 5]>>>,
+}
+
+impl CachedFilter {
+    fn new(filter: EnvFilter, static_filter: bool) -> CachedFilter {
+        CachedFilter { filter, cache: static_filter.then(Default::default) }
+    }
+
+    fn cached(&self, metadata: &Metadata<'_>, compute: impl FnOnce() -> bool) -> bool {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return compute(),
+        };
+        let level = match *metadata.level() {
+            Level::TRACE => 0,
+            Level::DEBUG => 1,
+            Level::INFO => 2,
+            Level::WARN => 3,
+            Level::ERROR => 4,
+        };
+        let target = metadata.target();
+        let cached = cache.read().unwrap_or_else(PoisonError::into_inner).get(target).copied();
+        if let Some(enabled) = cached.and_then(|levels| levels[level]) {
+            return enabled;
+        }
+        let enabled = compute();
+        let mut cache = cache.write().unwrap_or_else(PoisonError::into_inner);
+        cache.entry(target.to_owned()).or_default()[level] = Some(enabled);
+        enabled
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CachedFilter {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        Layer::<S>::register_callsite(&self.filter, metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Layer::<S>::max_level_hint(&self.filter)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.cached(metadata, || Layer::enabled(&self.filter, metadata, ctx))
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        Layer::on_new_span(&self.filter, attrs, id, ctx)
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        Layer::on_record(&self.filter, id, values, ctx)
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        Layer::on_enter(&self.filter, id, ctx)
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        Layer::on_exit(&self.filter, id, ctx)
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        Layer::on_close(&self.filter, id, ctx)
+    }
+}
+
+#[derive(Debug, Default)]
+struct LoggerFormatter {
+    format: LogFormat,
+    thread_names: bool,
+    locations: bool,
+    tag: Option<String>,
+}
 
 impl<S, N> FormatEvent<S, N> for LoggerFormatter
 where
@@ -119,15 +995,43 @@ where
         // always "log". `tracing-log` has hard coded it for some reason, so we
         // need to extract it using `normalized_metadata` method which is part of
         // `tracing_log::NormalizeEvent`.
-        let target = match event.normalized_metadata() {
+        let normalized = event.normalized_metadata();
+        let metadata = match &normalized {
             // This event is issued from `log` crate
-            Some(log) => log.target(),
-            None => event.metadata().target(),
+            Some(log) => log,
+            None => event.metadata(),
         };
-        write!(writer, "[{level} {target}] ")?;
+        if let Some(tag) = &self.tag {
+            write!(writer, "[{tag}] ")?;
+        }
+        write!(writer, "[{level}")?;
+        if self.format == LogFormat::Full {
+            write!(writer, " {}", metadata.target())?;
+        }
+        if self.locations {
+            if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+                write!(writer, " ({file}:{line})")?;
+            }
+        }
+        write!(writer, "] ")?;
+
+        if self.thread_names {
+            let thread = thread::current();
+            match thread.name() {
+                Some(name) => write!(writer, "({name}) ")?,
+                None => write!(writer, "({:?}) ", thread.id())?,
+            }
+        }
+        rust_analyzer::request_id::with(|id| match id {
+            Some(id) => write!(writer, "(request {id}) "),
+            None => Ok(()),
+        })?;
 
         // Write spans and fields of each span
         ctx.visit_spans(|span| {
+            if self.format == LogFormat::Compact {
+                return Ok(());
+            }
             write!(writer, "{}", span.name())?;
 
             let ext = span.extensions();
@@ -153,3 +1057,571 @@ where
         writeln!(writer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Instant};
+
+    use rust_analyzer::request_id;
+
+    use super::*;
+
+    #[test]
+    fn periodic_flush_reaches_disk() {
+        let path = std::env::temp_dir().join(format!("ra-logger-{}.log", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let interval = Duration::from_millis(50);
+
+        // Keep a handle around, so that the buffer isn't flushed by being dropped.
+        let writer = MakeWriterPeriodicFlush::spawn(file, interval, drop);
+        let subscriber = Registry::default().with(
+            tracing_subscriber::fmt::layer()
+                .event_format(LoggerFormatter::default())
+                .with_writer(writer.clone()),
+        );
+        let start = Instant::now();
+        tracing::subscriber::with_default(subscriber, || tracing::info!("periodic flush"));
+
+        let mut contents = String::new();
+        while start.elapsed() < interval * 10 {
+            contents = fs::read_to_string(&path).unwrap();
+            if !contents.is_empty() {
+                break;
+            }
+            thread::sleep(interval / 5);
+        }
+        drop(writer);
+        let _ = fs::remove_file(&path);
+
+        assert!(start.elapsed() < interval * 10, "record was not flushed in time");
+        assert!(contents.contains("periodic flush"), "unexpected log contents: {contents:?}");
+    }
+
+    #[test]
+    fn buffered_records_are_flushed_on_request() {
+        let path = std::env::temp_dir().join(format!("ra-logger-flush-{}.log", std::process::id()));
+        let mut logger = Logger::builder()
+            .file(File::create(&path).unwrap())
+            .buffering(Some(Duration::from_secs(3600)))
+            .build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || tracing::info!(target: "ra", "last"));
+        logger.flush();
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(contents, "[INFO ra] last\n");
+    }
+
+    #[test]
+    fn short_flush_intervals_are_raised() {
+        let logger = Logger::builder().buffering(Some(Duration::ZERO)).build();
+        assert_eq!(logger.flush_interval, Some(MIN_FLUSH_INTERVAL));
+    }
+
+    #[test]
+    fn empty_queues_are_raised() {
+        let logger = Logger::builder().queue(0, Backpressure::Drop).build();
+        assert_eq!(logger.queue, Some((1, Backpressure::Drop)));
+    }
+
+    #[test]
+    fn queued_records_reach_disk() {
+        const THREADS: usize = 4;
+        const RECORDS: usize = 200;
+
+        let path = std::env::temp_dir().join(format!("ra-logger-queue-{}.log", std::process::id()));
+        let mut logger = Logger::builder()
+            .file(File::create(&path).unwrap())
+            .queue(8, Backpressure::Block)
+            .build();
+        let dispatch = tracing::Dispatch::new(Registry::default().with(logger.fmt_layer()));
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let dispatch = &dispatch;
+                s.spawn(move || {
+                    tracing::dispatcher::with_default(dispatch, || {
+                        for i in 0..RECORDS {
+                            tracing::info!(target: "ra", "{t} {i}");
+                        }
+                    })
+                });
+            }
+        });
+        // The writer thread exits once it wrote everything and the queue is gone.
+        drop(dispatch);
+
+        let start = Instant::now();
+        let mut lines = 0;
+        while start.elapsed() < Duration::from_secs(10) {
+            lines = fs::read_to_string(&path).unwrap().lines().count();
+            if lines == THREADS * RECORDS {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, THREADS * RECORDS);
+    }
+
+    #[test]
+    fn flush_writes_out_queued_records() {
+        const RECORDS: usize = 200;
+
+        let path =
+            std::env::temp_dir().join(format!("ra-logger-queue-flush-{}.log", std::process::id()));
+        let mut logger = Logger::builder()
+            .file(File::create(&path).unwrap())
+            .queue(8, Backpressure::Block)
+            .build();
+        let dispatch = tracing::Dispatch::new(Registry::default().with(logger.fmt_layer()));
+        tracing::dispatcher::with_default(&dispatch, || {
+            for i in 0..RECORDS {
+                tracing::info!(target: "ra", "{i}");
+            }
+        });
+        // The queue is still around, only flushing makes sure it is written out.
+        logger.flush();
+
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, RECORDS);
+    }
+
+    #[test]
+    fn logs_to_any_writer() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger =
+            Logger::builder().writer(TestWriter(buf.clone())).filter("ra=info").build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "ra", "to memory");
+        });
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(contents, "[INFO ra] to memory\n");
+    }
+
+    #[test]
+    fn banner_is_logged_when_info_is_enabled() {
+        let logged = |filter: &str| {
+            let path = std::env::temp_dir()
+                .join(format!("ra-logger-banner-{filter}-{}.log", std::process::id()));
+            let mut logger =
+                Logger::builder().file(File::create(&path).unwrap()).filter(filter).build();
+            let banner = logger.banner();
+            let filter = CachedFilter::new(std::mem::take(&mut logger.filter), true);
+            let subscriber = Registry::default().with(filter).with(logger.fmt_layer());
+            tracing::subscriber::with_default(subscriber, || log_banner(&banner));
+            let contents = fs::read_to_string(&path).unwrap();
+            let _ = fs::remove_file(&path);
+            (banner, contents)
+        };
+
+        let (banner, contents) = logged("info");
+        assert!(banner.ends_with("logging with filter `info`, buffering off"));
+        assert_eq!(contents, format!("[INFO rust_analyzer::logger] {banner}\n"));
+
+        let (_, contents) = logged("warn");
+        assert_eq!(contents, "");
+    }
+
+    #[test]
+    fn malformed_filter_directives_are_reported() {
+        let path = std::env::temp_dir()
+            .join(format!("ra-logger-filter-warnings-{}.log", std::process::id()));
+        let mut logger = Logger::builder()
+            .file(File::create(&path).unwrap())
+            .filter("warn,ra::x=loud,ra=info")
+            .build();
+        assert_eq!(logger.filter_warnings.len(), 1);
+        assert!(logger.filter_warnings[0].starts_with("ignoring `ra::x=loud` in the log filter"));
+
+        let filter = CachedFilter::new(std::mem::take(&mut logger.filter), true);
+        let subscriber = Registry::default().with(filter).with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            log_filter_warnings(&logger.filter_warnings);
+            tracing::info!(target: "ra", "still filtered by the valid directives");
+        });
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            contents,
+            format!(
+                "[WARN rust_analyzer::logger] {}\n[INFO ra] still filtered by the valid directives\n",
+                logger.filter_warnings[0]
+            )
+        );
+    }
+
+    #[test]
+    fn request_id_is_included_while_set() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger =
+            Logger::builder().writer(TestWriter(buf.clone())).filter("ra=info").build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            {
+                let _guard = request_id::enter("42".to_owned());
+                {
+                    let _guard = request_id::enter("textDocument/hover:7".to_owned());
+                    tracing::info!(target: "ra", "nested");
+                }
+                tracing::info!(target: "ra", "in request");
+            }
+            tracing::info!(target: "ra", "after request");
+        });
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            contents,
+            "[INFO ra] (request textDocument/hover:7) nested\n\
+             [INFO ra] (request 42) in request\n\
+             [INFO ra] after request\n"
+        );
+    }
+
+    #[test]
+    fn compact_format_omits_target_and_spans() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::builder()
+            .writer(TestWriter(buf.clone()))
+            .filter("ra=info")
+            .format(LogFormat::Compact)
+            .build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!(target: "ra", "span", id = 1).entered();
+            tracing::info!(target: "ra", "in span");
+        });
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(contents, "[INFO] in span\n");
+    }
+
+    #[test]
+    fn builder_configures_logger() {
+        let logger = Logger::builder()
+            .filter("ra::a=info,ra::b[span]=trace")
+            .buffering(Some(Duration::from_millis(10)))
+            .format(LogFormat::Compact)
+            .thread_names(true)
+            .recent_records(3)
+            .build();
+
+        assert!(logger.writer.is_none());
+        assert!(!logger.static_filter);
+        assert_eq!(logger.flush_interval, Some(Duration::from_millis(10)));
+        assert_eq!(logger.format, LogFormat::Compact);
+        assert!(logger.thread_names);
+        assert_eq!(logger.recent.as_ref().map(|it| it.capacity), Some(3));
+        assert!(logger.sinks.is_none());
+    }
+
+    #[test]
+    fn new_delegates_to_builder() {
+        let logger = Logger::new(None, Some("ra[span]=info"), Some(Duration::from_millis(10)));
+
+        assert!(logger.writer.is_none());
+        assert!(!logger.static_filter);
+        assert_eq!(logger.flush_interval, Some(Duration::from_millis(10)));
+        assert!(!logger.thread_names);
+    }
+
+    #[test]
+    fn file_path_truncates_file() {
+        let path = std::env::temp_dir().join(format!("ra-logger-path-{}.log", std::process::id()));
+        fs::write(&path, "previous\n").unwrap();
+
+        let logger = Logger::builder().file_path(&path).unwrap().build();
+        logger.writer.unwrap().lock().unwrap().write_all(b"next\n").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(contents, "next\n");
+    }
+
+    #[test]
+    fn file_path_appending_appends_to_file() {
+        let path =
+            std::env::temp_dir().join(format!("ra-logger-append-{}.log", std::process::id()));
+        fs::write(&path, "previous\n").unwrap();
+
+        let logger = Logger::builder().file_path_appending(&path).unwrap().build();
+        logger.writer.unwrap().lock().unwrap().write_all(b"next\n").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(contents, "previous\nnext\n");
+    }
+
+    #[test]
+    fn file_path_reports_missing_directory() {
+        let path = std::env::temp_dir()
+            .join(format!("ra-logger-missing-{}", std::process::id()))
+            .join("ra.log");
+
+        let err = Logger::builder().file_path(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains(&*path.to_string_lossy()), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn thread_names_are_logged() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer = {
+            let buf = buf.clone();
+            move || TestWriter(buf.clone())
+        };
+        let subscriber = Registry::default().with(
+            tracing_subscriber::fmt::layer()
+                .event_format(LoggerFormatter { thread_names: true, ..Default::default() })
+                .with_writer(writer),
+        );
+
+        thread::Builder::new()
+            .name("Worker-2".to_owned())
+            .spawn(move || {
+                tracing::subscriber::with_default(subscriber, || tracing::info!("from a worker"))
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(contents.contains("] (Worker-2) from a worker"), "unexpected log: {contents:?}");
+    }
+
+    #[test]
+    fn concurrent_records_are_not_torn() {
+        const THREADS: usize = 8;
+        const RECORDS: usize = 100;
+
+        // through the stderr writer, which locks the stream for each record
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::builder().stderr(buf.clone()).build();
+        let dispatch = tracing::Dispatch::new(Registry::default().with(logger.fmt_layer()));
+        let padding = "x".repeat(200);
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let (dispatch, padding) = (&dispatch, &padding);
+                s.spawn(move || {
+                    tracing::dispatcher::with_default(dispatch, || {
+                        for i in 0..RECORDS {
+                            tracing::info!(target: "ra::test", "{t} {i} {padding}");
+                        }
+                    })
+                });
+            }
+        });
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let mut seen = vec![[false; RECORDS]; THREADS];
+        for line in contents.lines() {
+            let record =
+                line.strip_prefix("[INFO ra::test] ").and_then(|it| it.strip_suffix(&padding));
+            let (t, i) = record
+                .and_then(|it| it.trim_end().split_once(' '))
+                .and_then(|(t, i)| Some((t.parse::<usize>().ok()?, i.parse::<usize>().ok()?)))
+                .unwrap_or_else(|| panic!("torn line: {line:?}"));
+            seen[t][i] = true;
+        }
+        assert!(seen.iter().flatten().all(|&it| it), "missing records");
+    }
+
+    #[test]
+    fn locations_are_logged() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer = {
+            let buf = buf.clone();
+            move || TestWriter(buf.clone())
+        };
+        let subscriber = Registry::default().with(
+            tracing_subscriber::fmt::layer()
+                .event_format(LoggerFormatter { locations: true, ..Default::default() })
+                .with_writer(writer),
+        );
+
+        let line = line!() + 1;
+        tracing::subscriber::with_default(subscriber, || tracing::info!(target: "ra", "located"));
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(contents, format!("[INFO ra ({}:{line})] located\n", file!()));
+    }
+
+    #[test]
+    fn tag_is_logged() {
+        // to the log file
+        let path = std::env::temp_dir().join(format!("ra-logger-tag-{}.log", std::process::id()));
+        let mut logger = Logger::builder().file(File::create(&path).unwrap()).tag("ws:foo").build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || tracing::info!(target: "ra", "to file"));
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(contents, "[ws:foo] [INFO ra] to file\n");
+
+        // to stderr
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::builder().stderr(buf.clone()).tag("ws:foo").build();
+        assert!(logger.writer.is_none());
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || tracing::info!(target: "ra", "to stderr"));
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(contents, "[ws:foo] [INFO ra] to stderr\n");
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        }
+    }
+
+    #[test]
+    fn falls_back_to_stderr_when_the_file_fails() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::builder().writer(FailingWriter).stderr(buf.clone()).build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..MAX_FILE_ERRORS + 2 {
+                tracing::info!(target: "ra", "record {i}");
+            }
+        });
+
+        assert!(logger.file_errors.fell_back.load(Ordering::Relaxed));
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 4, "unexpected log: {contents:?}");
+        assert!(lines[0].contains("logging to stderr instead"), "unexpected log: {contents:?}");
+        assert_eq!(lines[1..], ["[INFO ra] record 2", "[INFO ra] record 3", "[INFO ra] record 4"]);
+    }
+
+    #[test]
+    fn buffered_logs_fall_back_to_stderr_when_flushing_fails() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::builder()
+            .writer(FailingWriter)
+            .stderr(buf.clone())
+            .buffering(Some(MIN_FLUSH_INTERVAL))
+            .build();
+        let subscriber = Registry::default().with(logger.fmt_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            // the buffer takes the records, only the periodic flushes fail
+            let start = Instant::now();
+            while !logger.file_errors.fell_back.load(Ordering::Relaxed) {
+                assert!(start.elapsed() < Duration::from_secs(10), "never fell back");
+                tracing::info!(target: "ra", "buffered");
+                thread::sleep(MIN_FLUSH_INTERVAL);
+            }
+            tracing::info!(target: "ra", "after the fallback");
+        });
+
+        let contents = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert!(lines[0].contains("logging to stderr instead"), "unexpected log: {contents:?}");
+        assert_eq!(lines.last(), Some(&"[INFO ra] after the fallback"));
+    }
+
+    struct TestWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recent_records_are_bounded() {
+        let logger = Logger::builder().recent_records(2).build();
+        let subscriber = Registry::default().with(logger.recent_layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::info!("record {i}");
+            }
+        });
+
+        let recent = logger.recent();
+        assert_eq!(recent.len(), 2, "unexpected records: {recent:?}");
+        assert!(recent[0].ends_with("record 3"), "unexpected records: {recent:?}");
+        assert!(recent[1].ends_with("record 4"), "unexpected records: {recent:?}");
+    }
+
+    #[test]
+    fn sinks_are_split_by_level() {
+        let dir = std::env::temp_dir();
+        let errors = dir.join(format!("ra-logger-errors-{}.log", std::process::id()));
+        let full = dir.join(format!("ra-logger-full-{}.log", std::process::id()));
+        let logger = Logger::builder()
+            .sink(LevelFilter::WARN, File::create(&errors).unwrap())
+            .sink(LevelFilter::TRACE, File::create(&full).unwrap())
+            .build();
+        let subscriber = Registry::default().with(logger.sinks_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!("an error");
+            tracing::info!("some info");
+        });
+
+        let errors_contents = fs::read_to_string(&errors).unwrap();
+        let full_contents = fs::read_to_string(&full).unwrap();
+        let _ = fs::remove_file(&errors);
+        let _ = fs::remove_file(&full);
+
+        let lines = |contents: &str| -> Vec<String> {
+            contents.lines().map(|line| line.rsplit("] ").next().unwrap().to_owned()).collect()
+        };
+        assert_eq!(lines(&errors_contents), vec!["an error".to_owned()]);
+        assert_eq!(lines(&full_contents), vec!["an error".to_owned(), "some info".to_owned()]);
+    }
+
+    struct CollectMetadata(Arc<Mutex<Vec<&'static Metadata<'static>>>>);
+
+    impl<S: Subscriber> Layer<S> for CollectMetadata {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            self.0.lock().unwrap().push(event.metadata());
+        }
+    }
+
+    #[test]
+    fn cached_filter_agrees_with_env_filter() {
+        const SPEC: &str = "warn,ra::a=info,ra::a::b=trace";
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = Registry::default().with(CollectMetadata(records.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!(target: "ra::a::b", "trace");
+            tracing::debug!(target: "ra::a", "debug");
+            tracing::info!(target: "ra::a", "info");
+            tracing::info!(target: "ra::c", "info");
+            tracing::warn!(target: "ra::c", "warn");
+        });
+
+        let cached = Registry::default().with(CachedFilter::new(EnvFilter::new(SPEC), true));
+        let uncached = Registry::default().with(EnvFilter::new(SPEC));
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 5);
+        for metadata in records.iter() {
+            // the first lookup fills the cache, the second one is answered by it
+            for _ in 0..2 {
+                assert_eq!(
+                    cached.enabled(metadata),
+                    uncached.enabled(metadata),
+                    "{} {}",
+                    metadata.target(),
+                    metadata.level()
+                );
+            }
+        }
+    }
+}
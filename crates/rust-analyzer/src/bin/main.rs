@@ -8,13 +8,16 @@ mod logger;
 mod rustc_wrapper;
 
 use std::{
-    env, fs,
+    env, fs, panic,
     path::{Path, PathBuf},
     process,
+    sync::Arc,
+    time::Duration,
 };
 
 use lsp_server::Connection;
 use rust_analyzer::{cli::flags, config::Config, from_json, Result};
+use tracing::level_filters::LevelFilter;
 use vfs::AbsPathBuf;
 
 #[cfg(all(feature = "mimalloc"))]
@@ -42,7 +45,6 @@ fn main() {
 
     let flags = flags::RustAnalyzer::from_env_or_exit();
     if let Err(err) = try_main(flags) {
-        tracing::error!("Unexpected error: {}", err);
         eprintln!("{err}");
         process::exit(101);
     }
@@ -65,7 +67,16 @@ fn try_main(flags: flags::RustAnalyzer) -> Result<()> {
         log_file = Some(Path::new(env_log_file));
     }
 
-    setup_logging(log_file)?;
+    let logger = setup_logging(log_file)?;
+    let result = run(flags);
+    if let Err(err) = &result {
+        tracing::error!("Unexpected error: {}", err);
+    }
+    logger.flush();
+    result
+}
+
+fn run(flags: flags::RustAnalyzer) -> Result<()> {
     let verbosity = flags.verbosity();
 
     match flags.subcommand {
@@ -93,7 +104,7 @@ fn try_main(flags: flags::RustAnalyzer) -> Result<()> {
     Ok(())
 }
 
-fn setup_logging(log_file: Option<&Path>) -> Result<()> {
+fn setup_logging(log_file: Option<&Path>) -> Result<Arc<logger::Logger>> {
     if cfg!(windows) {
         // This is required so that windows finds our pdb that is placed right beside the exe.
         // By default it doesn't look at the folder the exe resides in, only in the current working
@@ -110,23 +121,70 @@ fn setup_logging(log_file: Option<&Path>) -> Result<()> {
         env::set_var("RUST_BACKTRACE", "short");
     }
 
-    let log_file = match log_file {
-        Some(path) => {
-            if let Some(parent) = path.parent() {
-                let _ = fs::create_dir_all(parent);
-            }
-            Some(fs::File::create(path)?)
-        }
-        None => None,
-    };
+    if let Some(parent) = log_file.and_then(Path::parent) {
+        let _ = fs::create_dir_all(parent);
+    }
     let filter = env::var("RA_LOG").ok();
+    // buffer file logs and flush them every `RA_LOG_FLUSH_INTERVAL` milliseconds, if set
+    let flush_interval = env::var("RA_LOG_FLUSH_INTERVAL")
+        .ok()
+        .and_then(|it| it.parse().ok())
+        .map(Duration::from_millis);
     // deliberately enable all `error` logs if the user has not set RA_LOG, as there is usually useful
     // information in there for debugging
-    logger::Logger::new(log_file, filter.as_deref().or(Some("error"))).install()?;
+    let mut builder = logger::Logger::builder()
+        .filter(filter.as_deref().unwrap_or("error"))
+        .buffering(flush_interval)
+        .format(match env::var("RA_LOG_FORMAT").as_deref() {
+            Ok("compact") => logger::LogFormat::Compact,
+            _ => logger::LogFormat::Full,
+        })
+        .thread_names(env::var("RA_LOG_THREAD_NAMES").is_ok())
+        .locations(env::var("RA_LOG_LOCATIONS").is_ok());
+    if let Some(path) = log_file {
+        // keep the logs of previous runs if `RA_LOG_APPEND` is set
+        builder = match env::var("RA_LOG_APPEND") {
+            Ok(_) => builder.file_path_appending(path)?,
+            Err(_) => builder.file_path(path)?,
+        };
+    }
+    // write the log file from a background thread through a queue of `RA_LOG_QUEUE` records, if set
+    if let Some(capacity) = env::var("RA_LOG_QUEUE").ok().and_then(|it| it.parse().ok()) {
+        let backpressure = match env::var("RA_LOG_QUEUE_DROP") {
+            Ok(_) => logger::Backpressure::Drop,
+            Err(_) => logger::Backpressure::Block,
+        };
+        builder = builder.queue(capacity, backpressure);
+    }
+    if let Ok(tag) = env::var("RA_LOG_TAG") {
+        builder = builder.tag(tag);
+    }
+    // keep the last `RA_LOG_RECENT` records around to dump them on panics, if set
+    let recent = env::var("RA_LOG_RECENT").ok().and_then(|it| it.parse().ok());
+    if let Some(capacity) = recent {
+        builder = builder.recent_records(capacity);
+    }
+    // additionally collect warnings and errors in `RA_LOG_ERRORS_FILE`, if set
+    if let Some(path) = env::var_os("RA_LOG_ERRORS_FILE") {
+        let file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        builder = builder.sink(LevelFilter::WARN, file);
+    }
+    let logger = Arc::new(builder.install()?);
+    if recent.is_some() {
+        let logger = logger.clone();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            eprintln!("recent log records:");
+            for record in logger.recent() {
+                eprintln!("{record}");
+            }
+            default_hook(info);
+        }));
+    }
 
     profile::init();
 
-    Ok(())
+    Ok(logger)
 }
 
 const STACK_SIZE: usize = 1024 * 1024 * 8;
@@ -61,6 +61,19 @@ config_data! {
         assist_emitMustUse: bool               = "false",
         /// Placeholder expression to use for missing expressions in assists.
         assist_expressionFillDefault: ExprFillDefaultDef              = "\"todo\"",
+        /// Maximum line width assists should produce, usually rustfmt's `max_width`.
+        /// Paths that would not fit when qualified in place are left to be imported instead.
+        /// Set to null to not limit the width.
+        assist_maxWidth: Option<usize>                                = "null",
+        /// Whether to offer qualifying a path which starts with an imported name, like
+        /// `mod2::Item` after `use mod1::mod2;`, with the full path of that name.
+        assist_qualifyImportedPathStart: bool                         = "false",
+        /// Whether to only offer the shortest path when qualifying an unresolved name,
+        /// instead of all the paths it can be qualified with.
+        assist_qualifyShortestPathOnly: bool                          = "false",
+        /// Whether to offer calling a method of a trait which isn't in scope through the trait,
+        /// like `Trait::method(&x)`.
+        assist_qualifyTraitMethods: bool                              = "true",
 
         /// Warm up caches on project load.
         cachePriming_enable: bool = "true",
@@ -1047,6 +1060,10 @@ impl Config {
         self.experimental("codeActionGroup")
     }
 
+    pub fn code_action_target(&self) -> bool {
+        self.experimental("codeActionTarget")
+    }
+
     pub fn local_docs(&self) -> bool {
         self.experimental("localDocs")
     }
@@ -1419,6 +1436,10 @@ impl Config {
             insert_use: self.insert_use_config(),
             prefer_no_std: self.data.imports_prefer_no_std,
             assist_emit_must_use: self.data.assist_emitMustUse,
+            max_width: self.data.assist_maxWidth,
+            qualify_shortest_path_only: self.data.assist_qualifyShortestPathOnly,
+            qualify_imported_path_start: self.data.assist_qualifyImportedPathStart,
+            qualify_trait_methods: self.data.assist_qualifyTraitMethods,
         }
     }
 
@@ -252,6 +252,8 @@ fn map_rust_child_diagnostic(
                         change_annotations: None,
                     }),
                     is_preferred: Some(is_preferred),
+                    diagnostics: None,
+                    target: None,
                     data: None,
                     command: None,
                 },
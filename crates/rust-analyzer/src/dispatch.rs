@@ -8,6 +8,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::{
     global_state::{GlobalState, GlobalStateSnapshot},
     main_loop::Task,
+    request_id,
     version::version,
     LspError, Result,
 };
@@ -50,6 +51,7 @@ impl<'a> RequestDispatcher<'a> {
         };
         let result = {
             let _pctx = stdx::panic_context::enter(panic_context);
+            let _request_id = request_id::enter(format!("{}:{}", R::METHOD, req.id));
             f(self.global_state, params)
         };
         if let Ok(response) = result_to_response::<R>(req.id, result) {
@@ -74,9 +76,11 @@ impl<'a> RequestDispatcher<'a> {
             None => return self,
         };
         let global_state_snapshot = self.global_state.snapshot();
+        let id = format!("{}:{}", R::METHOD, req.id);
 
         let result = panic::catch_unwind(move || {
             let _pctx = stdx::panic_context::enter(panic_context);
+            let _request_id = request_id::enter(id);
             f(global_state_snapshot, params)
         });
 
@@ -104,9 +108,11 @@ impl<'a> RequestDispatcher<'a> {
 
         self.global_state.task_pool.handle.spawn({
             let world = self.global_state.snapshot();
+            let id = format!("{}:{}", R::METHOD, req.id);
             move || {
                 let result = panic::catch_unwind(move || {
                     let _pctx = stdx::panic_context::enter(panic_context);
+                    let _request_id = request_id::enter(id);
                     f(world, params)
                 });
                 match thread_result_to_response::<R>(req.id.clone(), result) {
@@ -140,9 +146,11 @@ impl<'a> RequestDispatcher<'a> {
 
         self.global_state.task_pool.handle.spawn({
             let world = self.global_state.snapshot();
+            let id = format!("{}:{}", R::METHOD, req.id);
             move || {
                 let result = panic::catch_unwind(move || {
                     let _pctx = stdx::panic_context::enter(panic_context);
+                    let _request_id = request_id::enter(id);
                     f(world, params)
                 });
                 match thread_result_to_response::<R>(req.id.clone(), result) {
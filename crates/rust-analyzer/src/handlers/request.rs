@@ -1125,7 +1125,24 @@ pub(crate) fn handle_code_action(
     for (index, assist) in assists.into_iter().enumerate() {
         let resolve_data =
             if code_action_resolve_cap { Some((index, params.clone())) } else { None };
-        let code_action = to_proto::code_action(&snap, assist, resolve_data)?;
+        let diagnostics: Vec<_> = params
+            .context
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| match &diagnostic.code {
+                Some(lsp_types::NumberOrString::String(code)) => {
+                    assist.diagnostic_codes.contains(&code.as_str())
+                }
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        let target = assist.target;
+        let mut code_action = to_proto::code_action(&snap, assist, resolve_data)?;
+        code_action.diagnostics = (!diagnostics.is_empty()).then_some(diagnostics);
+        if snap.config.code_action_target() {
+            code_action.target = Some(to_proto::range(&line_index, target));
+        }
         res.push(code_action)
     }
 
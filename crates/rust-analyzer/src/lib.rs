@@ -44,6 +44,7 @@ mod handlers {
 
 pub mod config;
 pub mod lsp_ext;
+pub mod request_id;
 
 #[cfg(test)]
 mod integrated_benchmarks;
@@ -405,6 +405,10 @@ pub struct CodeAction {
     pub edit: Option<SnippetWorkspaceEdit>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_preferred: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<lsp_types::Diagnostic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<lsp_types::Range>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<CodeActionData>,
@@ -0,0 +1,33 @@
+//! The id of the request the current thread works on, so that the logs can
+//! correlate the records of a request, see [`enter`].
+
+use std::cell::RefCell;
+
+thread_local! {
+    static REQUEST_ID: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Marks the current thread as working on the request `id`, until the
+/// returned guard is dropped.
+pub fn enter(id: String) -> RequestId {
+    let previous = REQUEST_ID.with(|it| it.borrow_mut().replace(id));
+    RequestId { previous }
+}
+
+/// Calls `f` with the id of the request the current thread works on, if any.
+pub fn with<T>(f: impl FnOnce(Option<&str>) -> T) -> T {
+    REQUEST_ID.with(|it| f(it.borrow().as_deref()))
+}
+
+/// Restores the previous request id of the thread when dropped.
+#[must_use]
+pub struct RequestId {
+    previous: Option<String>,
+}
+
+impl Drop for RequestId {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        REQUEST_ID.with(|it| *it.borrow_mut() = previous);
+    }
+}
@@ -1094,6 +1094,8 @@ pub(crate) fn code_action(
         kind: Some(code_action_kind(assist.id.1)),
         edit: None,
         is_preferred: None,
+        diagnostics: None,
+        target: None,
         data: None,
         command: None,
     };